@@ -0,0 +1,128 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::task::{Priority, Status, Subtask, Task};
+use crate::utils::{duration_seconds, utc_timestamp};
+
+/// Copia de una [Task] con todos sus campos públicos, para que otros programas lean y creen
+/// tareas sin depender de los detalles internos. Se serializa igual que [Task], por lo que
+/// sirve para leer y escribir el archivo de tareas.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct TaskDto {
+    pub id: Uuid,
+    pub title: String,
+    pub description: String,
+    pub status: Status,
+    /// Instantes en UTC
+    #[serde(with = "utc_timestamp")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "utc_timestamp")]
+    pub modified_at: NaiveDateTime,
+    /// Fecha límite en hora local
+    #[serde(default)]
+    pub due_date: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, with = "utc_timestamp::option")]
+    pub completed_at: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub order: u32,
+    #[serde(default)]
+    pub subtasks: Vec<Subtask>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub color: Option<[u8; 3]>,
+    #[serde(default, with = "utc_timestamp::option")]
+    pub in_progress_since: Option<NaiveDateTime>,
+    #[serde(default, with = "duration_seconds")]
+    pub total_in_progress: Duration,
+    #[serde(default)]
+    pub depends_on: Vec<Uuid>,
+    #[serde(default)]
+    pub estimate_minutes: Option<u32>,
+    #[serde(default, with = "utc_timestamp::option")]
+    pub last_viewed_at: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub favorite: bool,
+}
+
+/// Motivo por el que un [TaskDto] no se puede convertir en [Task]
+#[derive(Debug, PartialEq)]
+pub enum DtoError {
+    /// El título está vacío o solo tiene espacios
+    EmptyTitle,
+    /// La fecha de modificación es anterior a la de creación
+    ModifiedBeforeCreated
+}
+
+impl Display for DtoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            DtoError::EmptyTitle => "La tarea no tiene título",
+            DtoError::ModifiedBeforeCreated => "La fecha de modificación es anterior a la de creación"
+        })
+    }
+}
+
+impl From<&Task> for TaskDto {
+    fn from(task: &Task) -> Self {
+        TaskDto {
+            id: task.id(),
+            title: task.title.clone(),
+            description: task.description.clone(),
+            status: task.status,
+            created_at: task.created_at(),
+            modified_at: task.modified_at(),
+            due_date: task.due_date,
+            priority: task.priority,
+            tags: task.tags.clone(),
+            completed_at: task.completed_at(),
+            order: task.order,
+            subtasks: task.subtasks.clone(),
+            archived: task.archived,
+            color: task.color,
+            in_progress_since: task.in_progress_since(),
+            total_in_progress: task.total_in_progress(),
+            depends_on: task.depends_on.clone(),
+            estimate_minutes: task.estimate_minutes,
+            last_viewed_at: task.last_viewed_at,
+            favorite: task.favorite
+        }
+    }
+}
+
+impl TryFrom<TaskDto> for Task {
+    type Error = DtoError;
+
+    fn try_from(dto: TaskDto) -> Result<Self, Self::Error> {
+        if dto.title.trim().is_empty() {
+            return Err(DtoError::EmptyTitle);
+        }
+        if dto.modified_at < dto.created_at {
+            return Err(DtoError::ModifiedBeforeCreated);
+        }
+
+        let mut task = Task::restore(dto.id, dto.title, dto.description, dto.status, dto.created_at, dto.modified_at);
+        task.due_date = dto.due_date;
+        task.priority = dto.priority;
+        task.tags = dto.tags;
+        task.order = dto.order;
+        task.subtasks = dto.subtasks;
+        task.archived = dto.archived;
+        task.color = dto.color;
+        task.depends_on = dto.depends_on;
+        task.estimate_minutes = dto.estimate_minutes;
+        task.last_viewed_at = dto.last_viewed_at;
+        task.favorite = dto.favorite;
+        task.restore_tracking(dto.completed_at, dto.in_progress_since, dto.total_in_progress);
+
+        Ok(task)
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt::Display;
+use std::io::{self, Read, Write};
+use std::fs;
+use std::path::Path;
+
+use crate::task::Task;
+use crate::utils::{default_tasks_path, merge_tasks, parse_tasks, read_tasks, tasks_to_json, write_tasks, ReadError};
+
+/// Uso de los comandos, se muestra cuando los argumentos no son válidos
+pub const USAGE: &str = "Uso: task_tracker [export - | import -]";
+
+/// Comando de la línea de órdenes. Sin argumentos se abre la interfaz gráfica.
+/// * _`Export:`_ Escribe el archivo de tareas en la salida estándar
+/// * _`Import:`_ Lee tareas de la entrada estándar y las une con las guardadas, ver [merge_tasks]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Command {
+    Export,
+    Import
+}
+
+/// Errores de los comandos, se escriben en la salida de errores
+/// * _`Usage:`_ Los argumentos no corresponden a ningún comando
+/// * _`Read:`_ No se pudo leer el archivo de tareas
+/// * _`Io:`_ No se pudo leer la entrada, escribir la salida o guardar el archivo
+/// * _`Parse:`_ La entrada no es un JSON válido de tareas
+/// * _`Invalid:`_ El archivo de tareas a exportar no es válido, se deja sin cambios
+#[derive(Debug)]
+pub enum CliError {
+    Usage(String),
+    Read(ReadError),
+    Io(io::Error),
+    Parse(serde_json::Error),
+    Invalid(serde_json::Error)
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}\n{}", message, USAGE),
+            CliError::Read(error) => write!(f, "{}", error),
+            CliError::Io(error) => write!(f, "Error de entrada/salida: {}", error),
+            CliError::Parse(error) => write!(f, "La entrada no es un JSON válido de tareas: {}", error),
+            CliError::Invalid(error) => write!(f, "El archivo de tareas no es válido: {}", error),
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> Self {
+        CliError::Io(error)
+    }
+}
+
+impl From<ReadError> for CliError {
+    fn from(error: ReadError) -> Self {
+        CliError::Read(error)
+    }
+}
+
+impl CliError {
+    /// Código de salida del proceso: 2 para un uso incorrecto y 1 para los demás errores
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => 2,
+            _ => 1
+        }
+    }
+}
+
+/// Interpreta los argumentos, sin el nombre del programa. Retorna [None] si no hay argumentos
+/// y se debe abrir la interfaz gráfica. Por ahora solo se admite "-" (entrada o salida estándar).
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Command>, CliError> {
+    let args: Vec<String> = args.into_iter().collect();
+    let command = match args.first().map(String::as_str) {
+        None => return Ok(None),
+        Some("export") => Command::Export,
+        Some("import") => Command::Import,
+        Some(other) => return Err(CliError::Usage(format!("Comando desconocido: {}", other)))
+    };
+
+    match &args[1..] {
+        [target] if target == "-" => Ok(Some(command)),
+        [target] => Err(CliError::Usage(format!("Solo se admite \"-\", no \"{}\"", target))),
+        _ => Err(CliError::Usage("Se esperaba un único argumento \"-\"".to_string()))
+    }
+}
+
+/// Punto de entrada de los comandos con la entrada y salida estándar. Retorna el código de salida,
+/// o [None] si no hay comando y se debe abrir la interfaz gráfica. Los errores van a la salida de errores.
+pub fn main<I: IntoIterator<Item = String>>(args: I) -> Option<i32> {
+    let result = match parse_args(args) {
+        Ok(None) => return None,
+        Ok(Some(command)) => run(command, &default_tasks_path(), io::stdin().lock(), io::stdout().lock()),
+        Err(error) => Err(error)
+    };
+
+    Some(match result {
+        Ok(()) => 0,
+        Err(error) => {
+            eprintln!("{}", error);
+            error.exit_code()
+        }
+    })
+}
+
+/// Ejecuta `command` sobre el archivo de tareas en `path`, leyendo de `input` y escribiendo en `output`.
+/// La exportación solo lee el archivo: si no existe no se crea, y si no es válido no se renombra.
+pub fn run(command: Command, path: &Path, input: impl Read, output: impl Write) -> Result<(), CliError> {
+    match command {
+        Command::Export => {
+            let tasks = match fs::read_to_string(path) {
+                Ok(contents) => parse_tasks(&contents).map_err(CliError::Invalid)?,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+                Err(error) => return Err(CliError::Io(error))
+            };
+            export_to(&tasks, output)
+        }
+        Command::Import => {
+            let merged = import_from(input, read_tasks(path)?)?;
+            Ok(write_tasks(path, merged.iter().collect())?)
+        }
+    }
+}
+
+/// Escribe las tareas en `output` con el mismo formato que el archivo de tareas.
+/// Un fallo al serializar o escribir se reporta como [CliError::Io].
+pub fn export_to(tasks: &[Task], mut output: impl Write) -> Result<(), CliError> {
+    let json = tasks_to_json(tasks.iter().collect()).map_err(io::Error::from)?;
+    writeln!(output, "{}", json)?;
+    Ok(output.flush()?)
+}
+
+/// Lee un archivo de tareas de cualquier versión desde `input` y lo une con `local`, ver [merge_tasks]
+pub fn import_from(mut input: impl Read, local: Vec<Task>) -> Result<Vec<Task>, CliError> {
+    let mut contents = String::new();
+    input.read_to_string(&mut contents)?;
+
+    let incoming = parse_tasks(&contents).map_err(CliError::Parse)?;
+    Ok(merge_tasks(local, incoming))
+}
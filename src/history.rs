@@ -0,0 +1,64 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::task::Task;
+use crate::utils::utc_timestamp;
+
+/// Nombre del archivo de historial, guardado junto al archivo de tareas.
+pub const HISTORY_FILE: &str = "history.log";
+
+/// Operación registrada en el historial
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Operation {
+    Create,
+    Edit,
+    Delete
+}
+
+/// Línea del historial, una por operación, en formato JSON.
+/// * _`at:`_ Instante de la operación, en UTC
+/// * _`title:`_ Título de la tarea después de la operación (o antes de eliminarla)
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct HistoryEntry {
+    #[serde(with = "utc_timestamp")]
+    pub at: NaiveDateTime,
+    pub operation: Operation,
+    pub id: Uuid,
+    pub title: String,
+}
+
+impl HistoryEntry {
+    pub fn new(operation: Operation, task: &Task) -> Self {
+        HistoryEntry { at: Utc::now().naive_utc(), operation, id: task.id(), title: task.title.clone() }
+    }
+}
+
+/// Destino del historial: un archivo al que se agregan líneas, o una lista en memoria para las pruebas
+#[derive(Debug)]
+pub enum HistoryLog {
+    File(PathBuf),
+    Memory(Vec<HistoryEntry>)
+}
+
+impl HistoryLog {
+    /// Agrega una entrada al final del historial
+    pub fn append(&mut self, entry: HistoryEntry) -> io::Result<()> {
+        match self {
+            HistoryLog::File(path) => {
+                let line = serde_json::to_string(&entry)?;
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}", line)
+            }
+            HistoryLog::Memory(entries) => {
+                entries.push(entry);
+                Ok(())
+            }
+        }
+    }
+}
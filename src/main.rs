@@ -1,3 +1,7 @@
+pub mod api;
+pub mod cli;
+pub mod history;
+pub mod settings;
 pub mod utils;
 pub mod task_tracker;
 pub mod task;
@@ -5,21 +9,101 @@ pub mod task;
 #[cfg(test)]
 mod tests;
 
-use iced::{application, keyboard::{self, key::Named, Key}, widget::{button, column, container, focus_next, focus_previous, horizontal_space, row, scrollable, text, text_editor::Binding, text_editor, text_input}, window::Settings, Background, Element, Length, Size, Subscription, Theme};
-use task::Status;
-use task_tracker::{Message, TaskTracker};
-use utils::write_tasks;
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate};
+use iced::advanced::widget::{self, operate, operation::{focusable::Focusable, Operation, Outcome}};
+use iced::{application, event, time, keyboard::{self, key::Named, Key}, widget::{button, center, checkbox, column, container, focus_next, focus_previous, horizontal_space, mouse_area, vertical_space, opaque, progress_bar, row, scrollable, stack, text, text_editor::Binding, text_editor, text_input}, window::{self, Settings}, Background, Element, Event, Length, Point, Rectangle, Size, Subscription, Theme};
+use task::{Status, TaskView, ViewMode};
+use iced_aw::{helpers::date_picker, Wrap};
+use task_tracker::{FormErrors, ListRow, Message, RangeBound, SortBy, StatusFilter, TaskTracker};
+use settings::{load_settings, settings_path, ThemeMode, WindowState};
+use utils::{default_tasks_path, format_age, is_valid_date_time_format, parse_quick_add, DEFAULT_DATE_TIME_FORMAT};
+
+const MIN_SIZE: Size = Size::new(450.0, 580.0);
 
 fn main () -> iced::Result {
+    // Con un comando de la línea de órdenes no se abre la ventana
+    if let Some(code) = cli::main(std::env::args().skip(1)) {
+        std::process::exit(code);
+    }
+
+    let window = load_settings(&settings_path(&default_tasks_path())).window;
+
     application("Task Tracker", TaskTracker::update, TaskTracker::view)
     .window(Settings{
-        position: iced::window::Position::Centered,
-        min_size: Some(Size::new(450.0, 580.0)),
-        size: Size::new(450.0, 580.0),
+        position: match window {
+            Some(WindowState { x: Some(x), y: Some(y), .. }) => window::Position::Specific(Point::new(x, y)),
+            _ => window::Position::Centered
+        },
+        min_size: Some(MIN_SIZE),
+        size: window.map(|w| Size::new(w.width, w.height).max(MIN_SIZE)).unwrap_or(MIN_SIZE),
+        exit_on_close_request: false,
         ..Default::default()
     })
     .subscription(TaskTracker::subscriptions)
-    .run()
+    .theme(TaskTracker::theme)
+    .run_with(|| {
+        // Un error al cargar no cierra la aplicación, se muestra como advertencia
+        let tracker = TaskTracker::load().unwrap_or_else(TaskTracker::from_load_error);
+        (tracker, text_input::focus(title_input_id()))
+    })
+}
+
+/// Identificador del campo de título del formulario de creación, para darle el foco
+fn title_input_id() -> text_input::Id {
+    text_input::Id::new("title")
+}
+
+/// Identificador del campo de búsqueda, para darle el foco con Ctrl+F
+fn search_input_id() -> text_input::Id {
+    text_input::Id::new("search")
+}
+
+/// Operación que indica si algún widget tiene el foco. A diferencia de [widget::operation::focusable::find_focused]
+/// siempre produce un resultado, también cuando no hay foco.
+fn has_focus() -> impl Operation<bool> {
+    struct HasFocus(bool);
+
+    impl Operation<bool> for HasFocus {
+        fn focusable(&mut self, state: &mut dyn Focusable, _id: Option<&widget::Id>) {
+            self.0 |= state.is_focused();
+        }
+
+        fn container(&mut self, _id: Option<&widget::Id>, _bounds: Rectangle, operate_on_children: &mut dyn FnMut(&mut dyn Operation<bool>)) {
+            operate_on_children(self);
+        }
+
+        fn finish(&self) -> Outcome<bool> {
+            Outcome::Some(self.0)
+        }
+    }
+
+    HasFocus(false)
+}
+
+/// Operación que obtiene el id del widget con el foco, o [None] si no hay foco o el widget no tiene id.
+/// Como [has_focus], siempre produce un resultado.
+fn focused_widget() -> impl Operation<Option<widget::Id>> {
+    struct FocusedWidget(Option<widget::Id>);
+
+    impl Operation<Option<widget::Id>> for FocusedWidget {
+        fn focusable(&mut self, state: &mut dyn Focusable, id: Option<&widget::Id>) {
+            if state.is_focused() {
+                self.0 = id.cloned();
+            }
+        }
+
+        fn container(&mut self, _id: Option<&widget::Id>, _bounds: Rectangle, operate_on_children: &mut dyn FnMut(&mut dyn Operation<Option<widget::Id>>)) {
+            operate_on_children(self);
+        }
+
+        fn finish(&self) -> Outcome<Option<widget::Id>> {
+            Outcome::Some(self.0.clone())
+        }
+    }
+
+    FocusedWidget(None)
 }
 
 impl TaskTracker {
@@ -27,30 +111,323 @@ impl TaskTracker {
         match message {
             Message::FocusNext => return focus_next(),
             Message::FocusPrev => return focus_previous(),
+            Message::DismissWarning => self.warning = None,
+            Message::DismissNotice => self.notice = None,
+            Message::ExportMarkdown => self.export_markdown(),
+            Message::ExportCsv => self.export_csv(),
+            Message::ImportCsv => self.import_csv(),
+            Message::ExportJsonl => self.export_jsonl(),
+            Message::ImportJsonl => self.import_jsonl(),
+            Message::ImportExternal => {
+                let dialog = rfd::AsyncFileDialog::new()
+                    .set_title("Importar desde archivo")
+                    .add_filter("JSON", &["json"])
+                    .pick_file();
+                return iced::Task::perform(dialog, |file| Message::ExternalFilePicked(file.map(|file| file.path().to_path_buf())))
+            }
+            Message::ExternalFilePicked(Some(path)) => self.import_external_file(&path),
+            Message::ExternalFilePicked(None) => (),
+            Message::OpenStorageFolder => self.open_storage_folder(),
+            Message::UndoDelete => self.undo_delete(),
+            Message::UndoEdit(id) => self.undo_edit(id),
+            Message::BulkSetStatus(status) => {
+                let count = self.bulk_set_status(status);
+                if count > 0 {
+                    self.notice = Some(format!("{} tareas marcadas como {}", count, self.preferences.status_labels.get(status).singular.to_lowercase()));
+                }
+            }
+            Message::SetTheme(theme) => {
+                self.preferences.theme = Some(theme);
+                self.save_preferences();
+            }
+            Message::ListScrolled(viewport) => {
+                self.scroll_offset = viewport.absolute_offset().y;
+                self.viewport_height = viewport.bounds().height;
+            }
+            Message::ToggleToday => {
+                self.filter.date = match self.filter.date {
+                    Some(_) => None,
+                    None => Some(Local::now().date_naive())
+                };
+            }
+            Message::ToggleStats => self.show_stats = !self.show_stats,
+            Message::TogglePreferences => self.show_preferences = !self.show_preferences,
+            Message::ToggleAdvancedFilters => {
+                self.advanced_filters = !self.advanced_filters;
+                self.range_picker = None;
+            }
+            Message::SetRangePicker(picker) => self.range_picker = picker,
+            Message::SetCreatedRange(bound, day) => {
+                match bound {
+                    RangeBound::From => self.filter.created_from = day,
+                    RangeBound::To => self.filter.created_to = day
+                }
+                self.range_picker = None;
+            }
+            Message::ToggleSection(status) => self.open_sections.toggle(status),
+            Message::OpenCreate => {
+                self.creating = true;
+                return text_input::focus(title_input_id())
+            }
+            Message::CloseCreate => self.discard_draft(),
+            Message::ToggleDescriptionPreview => self.description_preview = !self.description_preview,
+            Message::SetCreateInModal(enabled) => {
+                self.preferences.create_in_modal = enabled;
+                self.save_preferences();
+            }
+            Message::SetViewMode(mode) => {
+                self.preferences.view_mode = mode;
+                self.save_preferences();
+            }
+
+            Message::WindowResized(size) => {
+                let window = self.window.get_or_insert(WindowState { width: size.width, height: size.height, x: None, y: None });
+                window.width = size.width;
+                window.height = size.height;
+            }
+            Message::WindowMoved(position) => {
+                let window = self.window.get_or_insert(WindowState { width: MIN_SIZE.width, height: MIN_SIZE.height, x: None, y: None });
+                window.x = Some(position.x);
+                window.y = Some(position.y);
+            }
+            Message::Flush => self.flush(),
+            Message::CheckFile => self.check_for_changes(),
+            Message::CloseRequested(id) => {
+                if self.has_unsaved_input() {
+                    self.pending_close = Some(id);
+                    return iced::Task::none()
+                }
+                return self.close(id)
+            }
+            Message::ConfirmClose => {
+                if let Some(id) = self.pending_close.take() {
+                    return self.close(id)
+                }
+            }
+            Message::CancelClose => self.pending_close = None,
+            Message::ClickAway => self.cancel_pending_deletes(),
+            Message::Escape => {
+                self.cancel_pending_deletes();
+                self.pending_clear = false;
+                self.pending_remove_filtered = false;
+                // Con el diálogo de cierre abierto, Escape solo lo cierra
+                if self.pending_close.take().is_some() {
+                    return iced::Task::none()
+                }
+                // El diálogo de creación se cierra descartando el borrador
+                if self.creating {
+                    self.discard_draft();
+                    return iced::Task::none()
+                }
+                // Se revisa qué campo tiene el foco por si es la búsqueda
+                return operate(focused_widget()).map(Message::EscapeFocused)
+            }
+            Message::EscapeFocused(id) => {
+                // Escape en la búsqueda la limpia y le quita el foco, sin tocar la edición de las tareas
+                if id == Some(search_input_id().into()) {
+                    self.filter.text.clear();
+                    return text_input::focus(text_input::Id::unique())
+                }
+                self.cancel_focused_edit();
+            }
+            Message::FocusSearch => return text_input::focus(search_input_id()),
+            Message::ToggleFocusedEdit => self.toggle_focused_edit(),
+            Message::SelectNext => self.select_adjacent(true),
+            Message::SelectPrev => self.select_adjacent(false),
+            // Los campos de texto no capturan las flechas, por lo que se revisa el foco como con Escape
+            Message::ArrowSelect(next) => return operate(has_focus()).map(move |focused| Message::ArrowSelectFocused(next, focused)),
+            Message::ArrowSelectFocused(next, false) => self.select_adjacent(next),
+            Message::ArrowSelectFocused(_, true) => (),
+            Message::RequestDeleteFocused => self.request_delete_focused(),
+            Message::SetAllExpanded(expanded) => self.set_all_expanded(expanded),
+            Message::RequestClearCompleted => self.pending_clear = true,
+            Message::CancelClearCompleted => self.pending_clear = false,
+            Message::ClearCompleted => {
+                self.pending_clear = false;
+                let count = self.clear_completed();
+                if count > 0 {
+                    self.notice = Some(format!("Se archivaron {} tareas terminadas", count));
+                }
+            }
+            Message::RequestRemoveFiltered => self.pending_remove_filtered = true,
+            Message::CancelRemoveFiltered => self.pending_remove_filtered = false,
+            Message::RemoveFiltered => {
+                self.pending_remove_filtered = false;
+                let count = self.remove_filtered();
+                if count > 0 {
+                    self.notice = Some(format!("Se eliminaron {} tareas", count));
+                }
+            }
             
             Message::Delete(id) => self.remove_task(id),
-            Message::SetDescription(action) => self.description.perform(action),
-            Message::SetTitle(title) => self.title = title,
+            Message::Duplicate(id) => {
+                self.duplicate_task(id);
+            }
+            Message::SetDescription(action) => {
+                self.description.perform(action);
+                if !self.description.text().trim().is_empty() {
+                    self.form_errors.description = false;
+                }
+            }
+            Message::SetTitle(title) => {
+                if !title.trim().is_empty() {
+                    self.form_errors.title = false;
+                }
+                // No se puede escribir más allá del largo máximo
+                self.title = title.chars().take(task::MAX_TITLE_CHARS).collect();
+                self.pending_create = None;
+            }
             Message::SetQueryText(text) => self.filter.text = text,
-            Message::SetQueryStatus(status)  => self.filter.status = status,
+            Message::ToggleQueryStatus(status)  => {
+                // Desde las archivadas se vuelve a las activas mostrando solo ese estado
+                if self.filter.archived {
+                    self.filter.status = StatusFilter::only(status);
+                    self.filter.archived = false;
+                }else {
+                    self.filter.status.toggle(status);
+                    self.filter.status = self.filter.status.or_all();
+                }
+            }
+            Message::ShowAllStatuses => {
+                self.filter.status = StatusFilter::default();
+                self.filter.archived = false;
+            }
+            Message::ShowArchived => {
+                self.filter.status = StatusFilter::default();
+                self.filter.archived = true;
+            }
+            Message::ToggleArchive(id) => self.toggle_archive(id),
+            Message::SetQueryTag(tag) => self.filter.tag = tag,
+            Message::SetSort(sort) => self.sort = sort,
+            Message::CreateFromForm => {
+                // Con el diálogo de creación cerrado no hay formulario visible desde el que crear
+                if self.preferences.create_in_modal && !self.creating {
+                    return iced::Task::none();
+                }
+
+                let description = self.description.text().trim().to_string();
+                return self.update(Message::Create(self.title.clone(), description))
+            }
             Message::Create(title, description) => {
-                if title.trim().is_empty() || description.trim().is_empty() {
+                // Se valida el título sin los atributos en línea, que es el que tendrá la tarea
+                let clean_title = parse_quick_add(&title).title;
+                let errors = FormErrors::validate(&clean_title, &description);
+                if errors.any() {
+                    self.form_errors = errors;
                     return iced::Task::none();
                 }
 
-                self.add_task(title, description);
-                self.title.clear();
-                self.description = text_editor::Content::new();
+                if self.preferences.warn_duplicates && self.has_duplicate_title(&clean_title) {
+                    self.pending_create = Some((title, description));
+                    return iced::Task::none();
+                }
+
+                return self.create_task(title, description);
+            }
+            Message::ConfirmCreate => {
+                if let Some((title, description)) = self.pending_create.take() {
+                    return self.create_task(title, description);
+                }
+            }
+            Message::CancelCreate => self.pending_create = None,
+            Message::SetWarnDuplicates(warn) => {
+                self.preferences.warn_duplicates = warn;
+                self.save_preferences();
+            }
+            Message::SetNotifications(enabled) => {
+                self.preferences.notifications = enabled;
+                self.save_preferences();
+                if enabled {
+                    return self.update(Message::Notify)
+                }
             }
+            Message::Notify => {
+                let notifications = self.take_notifications();
+                if !notifications.is_empty() {
+                    // Mostrar la notificación puede bloquear mientras responde el sistema
+                    std::thread::spawn(move || {
+                        for body in notifications {
+                            let _ = notify_rust::Notification::new().summary("Lista de Tareas").body(&body).show();
+                        }
+                    });
+                }
+            }
+            Message::SetAutoInProgress(auto) => {
+                self.preferences.auto_in_progress = auto;
+                self.save_preferences();
+            }
+            Message::SetEditNewTasks(enabled) => {
+                self.preferences.edit_new_tasks = enabled;
+                self.save_preferences();
+            }
+            Message::SetStrictTransitions(strict) => {
+                self.preferences.strict_transitions = strict;
+                self.save_preferences();
+            }
+            Message::SetHistoryLog(enabled) => {
+                self.preferences.history_log = enabled;
+                self.save_preferences();
+            }
+            Message::SetDateTimeFormat(format) => {
+                self.preferences.date_time_format = format;
+                self.save_preferences_later();
+            }
+            // Solo dígitos; el campo vacío desactiva la marca
+            Message::SetStaleAfterDays(days) if days.chars().all(|c| c.is_ascii_digit()) && days.len() <= 4 => {
+                self.preferences.stale_after_days = days.parse().unwrap_or(0);
+                self.save_preferences_later();
+            }
+            Message::SetStaleAfterDays(_) => (),
 
             Message::TaskMessage(id, task_message) => match task_message {
                 task::Message::Delete(id) => self.remove_task(id),
-                task::Message::Update => write_tasks(self.get_tasks()),
+                task::Message::Duplicate(id) => return self.update(Message::Duplicate(id)),
+                task::Message::ToggleArchive(id) => self.toggle_archive(id),
+                task::Message::ToggleFavorite(id) => self.toggle_favorite(id),
+                task::Message::CopyToClipboard(text) => {
+                    self.notice = Some("Tarea copiada al portapapeles".to_string());
+                    return iced::clipboard::write(text)
+                }
+                task::Message::FilterTag(tag) => self.filter.tag = Some(tag),
+                task::Message::MoveUp => self.move_task(id, true),
+                task::Message::MoveDown => self.move_task(id, false),
+                task::Message::Update => {
+                    if self.remove_cyclic_dependencies(id) {
+                        self.warning = Some("Se quitaron dependencias que formaban un ciclo".to_string());
+                    }
+                    self.save()
+                }
+                task::Message::Focus => self.focus_task(id),
                 _ => {
                     let task_view = self.tasks.iter_mut().find(|tv| tv.get_task().id() == id);
 
                     if let Some(task_view) = task_view {
-                        return task_view.update(task_message).map(move |m|Message::TaskMessage(id, m))
+                        task_view.set_auto_in_progress(self.preferences.auto_in_progress);
+                        task_view.set_strict_transitions(self.preferences.strict_transitions);
+                        let entering_edit = matches!(task_message, task::Message::ToggleState) && !task_view.is_editing();
+                        let before = matches!(task_message, task::Message::Modify { .. }).then(|| task_view.get_task().clone());
+                        let expanding = matches!(task_message, task::Message::ToggleExpanded) && !task_view.is_expanded();
+                        if entering_edit {
+                            task_view.set_status_labels(&self.preferences.status_labels);
+                        }
+                        let task = task_view.update(task_message).map(move |m|Message::TaskMessage(id, m));
+
+                        if entering_edit {
+                            // La tarea en edición queda seleccionada, así Escape sabe cuál cancelar
+                            self.focus_task(id);
+                            self.refresh_dependency_options(id);
+                        }
+                        // Abrir la tarea cambia `last_viewed_at`, que se guarda con la siguiente escritura diferida
+                        if entering_edit || expanding {
+                            self.save();
+                        }
+                        // Solo se registra si la edición cambió algo; el modo estricto puede haberla rechazado
+                        let changed = |before: &task::Task| self.get_task(id).is_some_and(|task| task.differs_from(before));
+                        if let Some(before) = before.filter(changed) {
+                            self.log_edit(&before);
+                            self.record_edit(before);
+                        }
+                        return task
                     }
                 }
             }
@@ -59,65 +436,140 @@ impl TaskTracker {
         iced::Task::none()
     }
 
-    fn view(&self) -> Element<Message> {
-        column![]
-        .push(text("Lista de Tareas").size(32))
-        .push(
-            text_input("Título...", &self.title).on_input(Message::SetTitle)
-            .on_submit(Message::FocusNext)
-        )
-        .push(text_editor(&self.description)
-            .placeholder("Descripción...")
-            .on_action(Message::SetDescription)
-            .key_binding(|key_press|{
-                if key_press.key == Key::Named(Named::Enter) && key_press.modifiers.shift() {
-                    return Some(Binding::Custom(Message::Create(self.title.clone(), self.description.text().trim().to_string())))
-                }
-                Binding::from_key_press(key_press)
+    fn view(&self) -> Element<'_, Message> {
+        let (to_do, in_progress, done) = self.status_counts();
+
+        let content = column![]
+        .push(row![]
+            .push(text("Lista de Tareas").size(32))
+            .push(horizontal_space())
+            .push(if self.pending_clear {
+                button(text!("¿Archivar {} terminadas?", done)).on_press(Message::ClearCompleted).style(button::danger)
+            }else {
+                button("Limpiar terminadas")
+                .on_press_maybe((done > 0).then_some(Message::RequestClearCompleted))
+                .style(button::secondary)
             })
-            
+            .push_maybe(self.pending_clear.then(|| {
+                button("Cancelar").on_press(Message::CancelClearCompleted).style(button::secondary)
+            }))
+            .spacing(5)
+            .align_y(iced::alignment::Vertical::Center)
         )
+        // Avance general
         .push(row![]
+            .push(progress_bar(0.0..=1.0, self.completion_ratio()).height(8))
+            .push(text!("{:.0}% completado", self.completion_ratio() * 100.0).size(12).style(text::secondary))
+            .push(
+                button(text(if self.show_stats {"Ocultar estadísticas"} else {"Estadísticas"}).size(12))
+                .on_press(Message::ToggleStats)
+                .style(button::text)
+                .padding(2)
+            )
             .push(
+                button(text(if self.show_preferences {"Preferencias ▾"} else {"Preferencias ▸"}).size(12))
+                .on_press(Message::TogglePreferences)
+                .style(button::text)
+                .padding(2)
+            )
+            .spacing(10)
+            .align_y(iced::alignment::Vertical::Center)
+        )
+        .push_maybe(self.show_stats.then(|| self.stats_panel()))
+        .push_maybe(self.show_preferences.then(|| self.preferences_panel()))
+        .push_maybe(self.warning.as_ref().map(|warning| {
+            container(
+                row![]
+                .push(text(warning).style(text::danger).width(Length::Fill))
+                .push(button("Cerrar").on_press(Message::DismissWarning).style(button::danger))
+                .spacing(10)
+            )
+            .style(container::rounded_box)
+            .padding(5)
+            .width(Length::Fill)
+        }))
+        .push_maybe(self.notice.as_ref().map(|notice| {
+            container(
+                row![]
+                .push(text(notice).width(Length::Fill))
+                .push(button("Cerrar").on_press(Message::DismissNotice).style(button::secondary))
+                .spacing(10)
+            )
+            .style(container::rounded_box)
+            .padding(5)
+            .width(Length::Fill)
+        }))
+        .push_maybe((!self.preferences.create_in_modal).then(|| self.create_form()))
+        .push(row![]
+            .push(if self.preferences.create_in_modal {
+                button("+ Nueva tarea").on_press(Message::OpenCreate)
+            }else {
                 button("Crear Tarea")
                 .on_press_with(|| Message::Create(self.title.clone(), self.description.text().trim().to_string()))
-            )
+            })
             .push(horizontal_space())
+            .push(
+                checkbox("Crear en un diálogo", self.preferences.create_in_modal)
+                .on_toggle(Message::SetCreateInModal)
+            )
+            .spacing(5)
+            .align_y(iced::alignment::Vertical::Center)
+        )
+        // Exportar e importar, en varias líneas si la ventana es angosta
+        .push(
+            Wrap::with_elements([
+                ("Exportar a Markdown", Message::ExportMarkdown),
+                ("Exportar CSV", Message::ExportCsv),
+                ("Importar CSV", Message::ImportCsv),
+                ("Exportar JSONL", Message::ExportJsonl),
+                ("Importar JSONL", Message::ImportJsonl),
+                ("Importar desde archivo...", Message::ImportExternal),
+                ("Abrir carpeta", Message::OpenStorageFolder),
+            ].into_iter().map(|(label, message)| button(label).on_press(message).style(button::secondary).into()).collect())
+            .spacing(5)
+            .line_spacing(5)
         )
-        
         .push(text("Buscar"))
         .push(
             text_input("Buscar por titulo o descripción...", &self.filter.text)
+            .id(search_input_id())
             .on_input(Message::SetQueryText)
         )
         .push(container(
                 row![]
                 .push(
-                    button("Todas").on_press(Message::SetQueryStatus(None))
-                    .style(if self.filter.status.is_none() {
+                    button(text!("Todas ({})", to_do + in_progress + done)).on_press(Message::ShowAllStatuses)
+                    .style(if !self.filter.archived && self.filter.status.is_all() {
                         button::primary
                     }else {button::secondary})
                 )
+                .extend(self.preferences.status_labels.order().into_iter().map(|status| {
+                    let count = match status {
+                        Status::ToDo => to_do,
+                        Status::InProgress => in_progress,
+                        Status::Done => done
+                    };
+                    self.status_button(format!("{} ({})", self.preferences.status_labels.get(status).plural, count), status).into()
+                }))
                 .push(
-                    button("Pendientes").on_press(Message::SetQueryStatus(Some(task::Status::ToDo)))
-                    .style(match self.filter.status {
-                        Some(status) if status == Status::ToDo => button::primary,
-                        _ => button::secondary
-                    })
+                    button(text!("Archivadas ({})", self.archived_tasks().count())).on_press(Message::ShowArchived)
+                    .style(if self.filter.archived {
+                        button::primary
+                    }else {button::secondary})
                 )
+                .push(horizontal_space())
                 .push(
-                    button("En progreso").on_press(Message::SetQueryStatus(Some(task::Status::InProgress)))
-                    .style(match self.filter.status {
-                        Some(status) if status == Status::InProgress => button::primary,
-                        _ => button::secondary
-                    })
+                    button("Hoy").on_press(Message::ToggleToday)
+                    .style(if self.filter.date.is_some() {
+                        button::primary
+                    }else {button::secondary})
                 )
                 .push(
-                    button("Terminadas").on_press(Message::SetQueryStatus(Some(task::Status::Done)))
-                    .style(match self.filter.status {
-                        Some(status) if status == Status::Done => button::primary,
-                        _ => button::secondary
-                    })
+                    button(if self.advanced_filters {"Filtros avanzados ▾"} else {"Filtros avanzados ▸"})
+                    .on_press(Message::ToggleAdvancedFilters)
+                    .style(if self.filter.has_created_range() {
+                        button::primary
+                    }else {button::secondary})
                 )
                 .spacing(5)
             ).style(|theme: &Theme| {
@@ -128,22 +580,185 @@ impl TaskTracker {
             .padding(5)
             .width(Length::Fill)
         )
-        .push(
+        .push_maybe(self.advanced_filters.then(|| self.advanced_filters_panel()))
+        .push_maybe(self.last_deleted.as_ref().map(|(_, task)| {
             container(
-                scrollable(
-                    column![]
-                    .extend(self.filtered_tasks())
-                    .spacing(5)
-                ).spacing(5)
-            ).height(Length::Fill)
+                row![]
+                .push(text!("Se eliminó \"{}\"", task.title).width(Length::Fill))
+                .push(button("Deshacer").on_press(Message::UndoDelete))
+                .spacing(10)
+                .align_y(iced::alignment::Vertical::Center)
+            )
+            .style(container::rounded_box)
+            .padding(5)
+            .width(Length::Fill)
+        }))
+        .push_maybe(self.last_edit.as_ref().filter(|_| self.can_undo_edit()).map(|(task, _)| {
+            container(
+                row![]
+                .push(text!("Se editó \"{}\"", task.title).width(Length::Fill))
+                .push(button("Deshacer cambios").on_press(Message::UndoEdit(task.id())))
+                .spacing(10)
+                .align_y(iced::alignment::Vertical::Center)
+            )
+            .style(container::rounded_box)
+            .padding(5)
+            .width(Length::Fill)
+        }))
+        .push_maybe(self.filter.tag.as_ref().map(|tag| {
+            row![]
+            .push(text!("Etiqueta: #{}", tag))
+            .push(button("Quitar filtro").on_press(Message::SetQueryTag(None)).style(button::text))
+            .spacing(5)
+            .align_y(iced::alignment::Vertical::Center)
+        }))
+        // Orden de la lista, como los filtros de estado, en varias líneas si la ventana es angosta
+        .push(
+            Wrap::with_elements(
+                std::iter::once(text("Ordenar:").into())
+                .chain(SortBy::ALL.iter().map(|sort| self.sort_button(*sort).into()))
+                .collect()
+            )
+            .spacing(5)
+            .line_spacing(5)
+            .align_items(iced::Alignment::Center)
+        )
+        // Acciones sobre la lista, en varias líneas si la ventana es angosta
+        .push(
+            Wrap::with_elements(vec![
+                {
+                    let compact = self.preferences.view_mode == ViewMode::Compact;
+                    button(if compact {"Vista normal"} else {"Vista compacta"})
+                    .on_press(Message::SetViewMode(if compact {ViewMode::Normal} else {ViewMode::Compact}))
+                    .style(button::secondary)
+                    .into()
+                },
+                button("Expandir todo").on_press(Message::SetAllExpanded(true)).style(button::secondary).into(),
+                button("Colapsar todo").on_press(Message::SetAllExpanded(false)).style(button::secondary).into(),
+                button("Marcar todas como terminadas")
+                .on_press_maybe(
+                    self.visible_tasks().iter().any(|tv| tv.get_task().status != Status::Done)
+                    .then_some(Message::BulkSetStatus(Status::Done))
+                )
+                .style(button::secondary)
+                .into(),
+                // Sin filtro no se ofrece, para no borrar la lista completa por error
+                if self.pending_remove_filtered {
+                    button(text!("¿Eliminar {} tareas?", self.visible_count())).on_press(Message::RemoveFiltered).style(button::danger)
+                }else {
+                    button("Eliminar filtradas")
+                    .on_press_maybe((self.filter.is_filtering() && self.visible_count() > 0).then_some(Message::RequestRemoveFiltered))
+                    .style(button::danger)
+                }.into(),
+            ].into_iter().chain(self.pending_remove_filtered.then(|| {
+                button("Cancelar").on_press(Message::CancelRemoveFiltered).style(button::secondary).into()
+            })).collect())
+            .spacing(5)
+            .line_spacing(5)
+        )
+        .push(
+            mouse_area(container(self.task_list()).height(Length::Fill))
+            .on_press(Message::ClickAway)
+        )
+        // Barra de estado
+        .push(
+            row![]
+            .push(text!("Mostrando {} de {} tareas", self.visible_count(), self.total_count()).style(text::secondary).size(12))
+            .push_maybe(Some(self.overdue().len()).filter(|count| *count > 0).map(|count| {
+                text!("{} vencidas", count).style(text::danger).size(12)
+            }))
+            .push_maybe(Some(self.due_today().len()).filter(|count| *count > 0).map(|count| {
+                text!("{} vencen hoy", count).style(text::secondary).size(12)
+            }))
+            .spacing(15)
         )
         .padding(15)
-        .spacing(5)
-        .into()   
+        .spacing(5);
+
+        // Confirmación al cerrar con cambios sin guardar, por encima del resto de la interfaz
+        if self.pending_close.is_none() {
+            return if self.creating && self.preferences.create_in_modal {
+                let dialog = container(
+                    column![]
+                    .push(text("Nueva tarea").size(20))
+                    .push(self.create_form())
+                    .push(row![]
+                        .push(horizontal_space())
+                        .push(button("Cancelar").on_press(Message::CloseCreate).style(button::secondary))
+                        .push(
+                            button("Crear Tarea")
+                            .on_press_with(|| Message::Create(self.title.clone(), self.description.text().trim().to_string()))
+                        )
+                        .spacing(10)
+                    )
+                    .spacing(10)
+                )
+                .style(container::rounded_box)
+                .padding(20)
+                .max_width(600);
+
+                modal(content, dialog)
+            }else {
+                content.into()
+            }
+        }
+
+        let dialog = container(
+            column![]
+            .push(text("Hay cambios sin guardar").size(20))
+            .push(text("El formulario o alguna tarea en edición tiene texto que se perderá al cerrar."))
+            .push(row![]
+                .push(horizontal_space())
+                .push(button("Seguir editando").on_press(Message::CancelClose).style(button::secondary))
+                .push(button("Cerrar de todos modos").on_press(Message::ConfirmClose).style(button::danger))
+                .spacing(10)
+            )
+            .spacing(10)
+        )
+        .style(container::rounded_box)
+        .padding(20)
+        .max_width(450);
+
+        modal(content, dialog)
     }
 
     fn subscriptions(&self) -> Subscription<Message> {
-        keyboard::on_key_press(|key, modifiers| {
+        let window_events = event::listen_with(|event, _status, id| match event {
+            Event::Window(window::Event::Resized(size)) => Some(Message::WindowResized(size)),
+            Event::Window(window::Event::Moved(position)) => Some(Message::WindowMoved(position)),
+            Event::Window(window::Event::CloseRequested) => Some(Message::CloseRequested(id)),
+            // Escape también sale de la edición aunque el foco esté en uno de sus campos
+            Event::Keyboard(keyboard::Event::KeyPressed { key: Key::Named(Named::Escape), .. }) => Some(Message::Escape),
+            // Ctrl+F lleva a la búsqueda aunque otro campo tenga el foco
+            Event::Keyboard(keyboard::Event::KeyPressed { key: Key::Character(c), modifiers, .. }) if modifiers.command() && c.as_str() == "f" => {
+                Some(Message::FocusSearch)
+            }
+            // Atajo global para crear, sin importar qué elemento tiene el foco
+            Event::Keyboard(keyboard::Event::KeyPressed { key: Key::Named(Named::Enter), modifiers, .. }) if modifiers.command() => {
+                Some(Message::CreateFromForm)
+            }
+            _ => None
+        });
+
+        // Solo se reciben las teclas que ningún campo de texto capturó
+        let keys = keyboard::on_key_press(|key, modifiers| {
+            // Enter o F2 editan la tarea seleccionada; Ctrl+Enter queda para crear
+            if (key == Key::Named(Named::Enter) && !modifiers.command()) || key == Key::Named(Named::F2) {
+                return Some(Message::ToggleFocusedEdit)
+            }
+
+            // Navegación entre tarjetas al estilo vim; las letras escritas en un campo de texto no llegan aquí
+            if modifiers.is_empty() {
+                match key.as_ref() {
+                    Key::Character("j") => return Some(Message::SelectNext),
+                    Key::Character("k") => return Some(Message::SelectPrev),
+                    Key::Named(Named::ArrowDown) => return Some(Message::ArrowSelect(true)),
+                    Key::Named(Named::ArrowUp) => return Some(Message::ArrowSelect(false)),
+                    Key::Character("d") => return Some(Message::RequestDeleteFocused),
+                    _ => ()
+                }
+            }
+
             if key == Key::Named(Named::Tab) {
                 if modifiers.shift() {
                     return Some(Message::FocusPrev)
@@ -153,27 +768,330 @@ impl TaskTracker {
             }
 
             None
-        })    
+        });
+
+        // Guardado diferido: como máximo una escritura cada 500ms mientras haya cambios pendientes
+        let flush = if self.is_dirty() {
+            time::every(Duration::from_millis(500)).map(|_| Message::Flush)
+        }else {
+            Subscription::none()
+        };
+
+        // Recarga en vivo si el archivo se edita o sincroniza desde fuera
+        let watch = time::every(Duration::from_secs(1)).map(|_| Message::CheckFile);
+
+        // Avisos de fechas límite, si están activados
+        let notify = if self.preferences.notifications {
+            time::every(Duration::from_secs(self.preferences.notify_interval_minutes.max(1) * 60)).map(|_| Message::Notify)
+        }else {
+            Subscription::none()
+        };
+
+        Subscription::batch([keys, window_events, flush, watch, notify])
     }
 
-    fn filtered_tasks(&self) -> Vec<iced::Element<Message>> {
-        let query = &self.filter.text;
-        match self.filter.status {
-            Some(status) => self.by_status(status)
-            .filter(|tv|{
-                let task = tv.get_task();
+    /// Encabezado de una sección de la lista agrupada, abre o cierra la sección al hacer click
+    fn section_header(&self, status: Status, count: usize) -> Element<'_, Message> {
+        let label = &self.preferences.status_labels.get(status).plural;
+        let arrow = if self.open_sections.contains(status) {"▾"} else {"▸"};
 
-                return task.title.contains(query) || task.description.contains(query)
-            })
-            .map(|task|task.view().map(|m| Message::TaskMessage(task.get_task().id(), m))).collect(),
-            None => self.tasks.iter()
-                .filter(|tv| {
-                    let task = tv.get_task();
-
-                    return task.title.contains(query) || task.description.contains(query)
-                })
-                .map(|task| task.view().map(|m|Message::TaskMessage(task.get_task().id(), m))).collect(),
+        button(text!("{} {} ({})", arrow, label, count).size(18))
+        .on_press(Message::ToggleSection(status))
+        .style(button::text)
+        .padding([5, 0])
+        .into()
+    }
+
+    fn task_card<'a>(&'a self, task: &'a TaskView) -> Element<'a, Message> {
+        let id = task.get_task().id();
+        task.view(&self.filter.text, self.is_blocked(id), self.is_stale(task.get_task()), self.preferences.view_mode, &self.preferences.status_labels, &self.preferences.date_time_format).map(move |m| Message::TaskMessage(id, m))
+    }
+
+    /// Lista de tareas filtradas, o un mensaje cuando no hay ninguna que mostrar.
+    /// En listas largas solo se construyen las tarjetas cercanas a la parte visible.
+    fn task_list(&self) -> Element<'_, Message> {
+        // El filtro y el orden siempre se aplican a todas las tareas
+        let rows = self.list_rows();
+
+        if rows.is_empty() {
+            let message = if self.tasks.is_empty() {
+                "No hay tareas todavía"
+            }else {
+                "Sin resultados para tu búsqueda"
+            };
+
+            return center(text(message).style(text::secondary)).into()
+        }
+
+        let item_height = match self.preferences.view_mode {
+            ViewMode::Normal => task_tracker::CARD_HEIGHT,
+            ViewMode::Compact => task_tracker::COMPACT_CARD_HEIGHT
+        };
+        let window = if rows.len() >= task_tracker::VIRTUALIZE_FROM {
+            task_tracker::visible_window(rows.len(), self.scroll_offset, self.viewport_height, item_height)
+        }else {
+            0..rows.len()
+        };
+
+        // Las tarjetas que no se construyen se reemplazan por espacio de su alto estimado
+        let before = window.start as f32 * item_height;
+        let after = (rows.len() - window.end) as f32 * item_height;
+
+        scrollable(
+            column![]
+            .push_maybe((before > 0.0).then(|| vertical_space().height(before)))
+            .extend(rows[window].iter().map(|row| match row {
+                ListRow::Section(status, count) => self.section_header(*status, *count),
+                ListRow::Task(task) => self.task_card(task)
+            }))
+            .push_maybe((after > 0.0).then(|| vertical_space().height(after)))
+            .spacing(5)
+        )
+        .on_scroll(Message::ListScrolled)
+        .spacing(5).into()
+    }
+
+    /// Guarda las tareas y el estado de la ventana, y la cierra
+    fn close(&mut self, id: window::Id) -> iced::Task<Message> {
+        self.flush();
+        self.save_window_state();
+        self.save_view_state();
+        window::close(id)
+    }
+
+    /// Crea la tarea, limpia el formulario y devuelve el foco al título para seguir agregando tareas.
+    /// Si está activado [settings::Preferences::edit_new_tasks], la tarea nueva queda seleccionada y en edición.
+    fn create_task(&mut self, title: String, description: String) -> iced::Task<Message> {
+        let id = self.add_quick_task(&title, description);
+        self.creating = false;
+        self.form_errors = FormErrors::default();
+        self.title.clear();
+        self.description = text_editor::Content::new();
+        self.description_preview = false;
+
+        let focus = text_input::focus(title_input_id());
+        if self.preferences.edit_new_tasks {
+            // Se cierran las ediciones sin cambios para no acumular tareas abiertas; las que tienen cambios se conservan
+            for task_view in self.tasks.iter_mut().filter(|tv| tv.is_editing() && !tv.has_changes()) {
+                let _ = task_view.update(task::Message::CancelEdit);
+            }
+            self.focus_task(id);
+            let edit = self.update(Message::TaskMessage(id, task::Message::ToggleState));
+            return iced::Task::batch([edit, focus])
         }
+        focus
+    }
+
+    /// Totales por estado, tareas creadas esta semana, antigüedad promedio de las abiertas y avance
+    fn stats_panel(&self) -> Element<'_, Message> {
+        let stats = self.stats();
+        let labels = &self.preferences.status_labels;
+        let stat = |label: String, value: String| {
+            column![]
+            .push(text(value).size(20))
+            .push(text(label).size(12).style(text::secondary))
+            .align_x(iced::alignment::Horizontal::Center)
+            .width(Length::Fill)
+        };
+
+        container(
+            row![]
+            .push(stat(labels.get(Status::ToDo).plural.clone(), stats.to_do.to_string()))
+            .push(stat(labels.get(Status::InProgress).plural.clone(), stats.in_progress.to_string()))
+            .push(stat(labels.get(Status::Done).plural.clone(), stats.done.to_string()))
+            .push(stat("Creadas esta semana".to_string(), stats.created_this_week.to_string()))
+            .push(stat("Antigüedad promedio".to_string(), stats.average_open_age.map_or("-".to_string(), format_age)))
+            .push(stat("Completado".to_string(), format!("{:.0}%", stats.completion_rate * 100.0)))
+            .spacing(10)
+        )
+        .style(container::rounded_box)
+        .padding(10)
+        .width(Length::Fill)
+        .into()
+    }
+
+    /// Opciones de la interfaz, ver [settings::Preferences]. En varias líneas si la ventana es angosta
+    fn preferences_panel(&self) -> Element<'_, Message> {
+        let dark = self.theme().extended_palette().is_dark;
+        let toggle = |label, checked, message: fn(bool) -> Message| checkbox(label, checked).on_toggle(message).into();
+
+        container(
+            column![]
+            .push(
+                Wrap::with_elements(vec![
+                    toggle("Avisar de títulos repetidos", self.preferences.warn_duplicates, Message::SetWarnDuplicates),
+                    toggle("Pasar a \"En progreso\" al editar", self.preferences.auto_in_progress, Message::SetAutoInProgress),
+                    toggle("Editar al crear", self.preferences.edit_new_tasks, Message::SetEditNewTasks),
+                    toggle("Estados en orden", self.preferences.strict_transitions, Message::SetStrictTransitions),
+                    toggle("Guardar historial", self.preferences.history_log, Message::SetHistoryLog),
+                    toggle("Notificaciones", self.preferences.notifications, Message::SetNotifications),
+                    button(if dark {"Modo claro"} else {"Modo oscuro"})
+                    .on_press(Message::SetTheme(if dark {ThemeMode::Light} else {ThemeMode::Dark}))
+                    .style(button::secondary)
+                    .into()
+                ])
+                .spacing(15)
+                .line_spacing(5)
+            )
+            // Formato de las fechas de las tarjetas
+            .push(row![]
+                .push(text("Formato de fecha:"))
+                .push(
+                    text_input(DEFAULT_DATE_TIME_FORMAT, &self.preferences.date_time_format)
+                    .on_input(Message::SetDateTimeFormat)
+                    .width(250)
+                )
+                .push_maybe((self.preferences.date_time_format != DEFAULT_DATE_TIME_FORMAT).then(|| {
+                    button("Restablecer")
+                    .on_press(Message::SetDateTimeFormat(DEFAULT_DATE_TIME_FORMAT.to_string()))
+                    .style(button::secondary)
+                }))
+                .spacing(5)
+                .align_y(iced::alignment::Vertical::Center)
+            )
+            .push_maybe((!is_valid_date_time_format(&self.preferences.date_time_format)).then(|| {
+                text("Formato no válido, se usa el predeterminado").size(12).style(text::danger)
+            }))
+            .push(row![]
+                .push(text("Sin actividad tras (días, 0 desactiva):"))
+                .push(
+                    text_input("30", &self.preferences.stale_after_days.to_string())
+                    .on_input(Message::SetStaleAfterDays)
+                    .width(60)
+                )
+                .spacing(5)
+                .align_y(iced::alignment::Vertical::Center)
+            )
+            .spacing(10)
+        )
+        .style(container::rounded_box)
+        .padding(10)
+        .width(Length::Fill)
+        .into()
+    }
+
+    /// Rango de fechas de creación, cada extremo se puede dejar sin límite
+    fn advanced_filters_panel(&self) -> Element<'_, Message> {
+        let bound_input = |label, bound, day: Option<NaiveDate>| {
+            row![]
+            .push(text(label))
+            .push(date_picker(
+                self.range_picker == Some(bound),
+                day.unwrap_or(Local::now().date_naive()),
+                button(text(day.map_or("Sin límite".to_string(), |day| day.format("%d/%m/%Y").to_string())))
+                .on_press(Message::SetRangePicker(Some(bound)))
+                .style(button::secondary),
+                Message::SetRangePicker(None),
+                move |date| Message::SetCreatedRange(bound, Some(NaiveDate::from(date)))
+            ))
+            .push_maybe(day.map(|_| button("Quitar").on_press(Message::SetCreatedRange(bound, None)).style(button::text)))
+            .spacing(5)
+            .align_y(iced::alignment::Vertical::Center)
+        };
+
+        container(
+            row![]
+            .push(bound_input("Creadas desde:", RangeBound::From, self.filter.created_from))
+            .push(bound_input("hasta:", RangeBound::To, self.filter.created_to))
+            .spacing(15)
+            .align_y(iced::alignment::Vertical::Center)
+        )
+        .style(container::rounded_box)
+        .padding(5)
+        .width(Length::Fill)
+        .into()
+    }
+
+    /// Campos para crear una tarea, junto al aviso de título repetido
+    fn create_form(&self) -> iced::widget::Column<'_, Message> {
+        column![]
+        .push(
+            text_input("Título... (!alta #etiqueta due:mañana)", &self.title).on_input(Message::SetTitle)
+            .id(title_input_id())
+            .on_submit(Message::FocusNext)
+            .style(|theme: &Theme, status| {
+                let mut style = text_input::default(theme, status);
+                if self.form_errors.title {
+                    style.border.color = theme.extended_palette().danger.base.color;
+                }
+                style
+            })
+        )
+        .push(
+            text!("{}/{}", self.title.chars().count(), task::MAX_TITLE_CHARS)
+            .size(12)
+            .style(if self.title.chars().count() >= task::MAX_TITLE_CHARS {text::danger} else {text::secondary})
+        )
+        .push_maybe(self.form_errors.title.then(|| text("El título es obligatorio").style(text::danger).size(12)))
+        .push_maybe(self.description_preview.then(|| container(task::description_preview(&self.description.text())).height(task::editor_height(&self.description))))
+        .push_maybe((!self.description_preview).then(|| text_editor(&self.description)
+            .placeholder("Descripción...")
+            .on_action(Message::SetDescription)
+            .height(task::editor_height(&self.description))
+            .style(|theme: &Theme, status| {
+                let mut style = text_editor::default(theme, status);
+                if self.form_errors.description {
+                    style.border.color = theme.extended_palette().danger.base.color;
+                }
+                style
+            })
+            .key_binding(|key_press|{
+                if key_press.key == Key::Named(Named::Enter) && key_press.modifiers.shift() {
+                    return Some(Binding::Custom(Message::Create(self.title.clone(), self.description.text().trim().to_string())))
+                }
+                // Ctrl/Cmd + Enter lo maneja el atajo global
+                if key_press.key == Key::Named(Named::Enter) && key_press.modifiers.command() {
+                    return None
+                }
+                Binding::from_key_press(key_press)
+            })
+        ))
+        .push(
+            row![]
+            .push(task::length_counter(&self.description.text(), task::DESCRIPTION_MAX_CHARS))
+            .push(horizontal_space())
+            .push(task::preview_toggle(self.description_preview, Message::ToggleDescriptionPreview))
+            .align_y(iced::alignment::Vertical::Center)
+        )
+        .push_maybe(self.form_errors.description.then(|| text("La descripción es obligatoria").style(text::danger).size(12)))
+        .push_maybe(self.pending_create.as_ref().map(|(title, _)| {
+            container(
+                row![]
+                .push(text!("Ya existe una tarea llamada \"{}\"", title.trim()).style(text::danger).width(Length::Fill))
+                .push(button("Crear de todos modos").on_press(Message::ConfirmCreate))
+                .push(button("Cancelar").on_press(Message::CancelCreate).style(button::secondary))
+                .spacing(10)
+                .align_y(iced::alignment::Vertical::Center)
+            )
+            .style(container::rounded_box)
+            .padding(5)
+            .width(Length::Fill)
+        }))
+        .spacing(5)
+    }
+
+    /// Botón que activa o desactiva un estado del filtro, resaltado si está activo
+    fn status_button(&self, label: String, status: Status) -> iced::widget::Button<'_, Message> {
+        button(text(label)).on_press(Message::ToggleQueryStatus(status))
+        .style(if !self.filter.archived && self.filter.status.contains(status) {
+            button::primary
+        }else {button::secondary})
+    }
 
+    fn sort_button(&self, sort: SortBy) -> iced::widget::Button<'_, Message> {
+        button(text(sort.to_string())).on_press(Message::SetSort(sort))
+        .style(if self.sort == sort {
+            button::primary
+        }else {button::secondary})
     }
-}
\ No newline at end of file
+}
+
+/// Muestra `dialog` centrado sobre `content`, oscureciendo y bloqueando el resto de la interfaz
+fn modal<'a>(content: impl Into<Element<'a, Message>>, dialog: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    stack![
+        content.into(),
+        opaque(
+            center(dialog).style(|_: &Theme| container::Style::default().background(iced::Color { a: 0.6, ..iced::Color::BLACK }))
+        )
+    ].into()
+}
@@ -0,0 +1,185 @@
+use std::{fs, io, path::{Path, PathBuf}};
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+
+use crate::task::{StatusLabels, ViewMode};
+use crate::task_tracker::{SortBy, StatusFilter};
+use crate::utils::{write_atomic, DEFAULT_DATE_TIME_FORMAT};
+
+/// Nombre del archivo de configuración, guardado junto al archivo de tareas.
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// Archivos usados por versiones anteriores, se leen solo si aún no existe "settings.json".
+const LEGACY_WINDOW_FILE: &str = "window.json";
+const LEGACY_PREFERENCES_FILE: &str = "preferences.json";
+
+/// Configuración de la aplicación, guardada por separado de las tareas.
+/// * _`preferences:`_ Preferencias de la interfaz
+/// * _`window:`_ Tamaño y posición de la ventana; si no es válido se usa el predeterminado
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct Settings {
+    #[serde(default, deserialize_with = "or_default")]
+    pub preferences: Preferences,
+    #[serde(default, deserialize_with = "valid_window")]
+    pub window: Option<WindowState>,
+}
+
+/// Tamaño y posición de la ventana guardados entre sesiones.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub struct WindowState {
+    pub width: f32,
+    pub height: f32,
+    pub x: Option<f32>,
+    pub y: Option<f32>,
+}
+
+/// Tema de colores elegido por el usuario
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeMode {
+    Light,
+    Dark
+}
+
+/// Preferencias de la interfaz.
+/// * _`theme:`_ Si es [None] se usa el tema del sistema
+/// * _`warn_duplicates:`_ Pedir confirmación al crear una tarea con un título que ya existe
+/// * _`view_mode:`_ Tarjetas completas o vista compacta
+/// * _`status_labels:`_ Nombres y orden de los estados en la interfaz
+/// * _`create_in_modal:`_ Crear las tareas desde un diálogo en lugar del formulario fijo
+/// * _`auto_in_progress:`_ Proponer "En progreso" al editar una tarea pendiente
+/// * _`edit_new_tasks:`_ Abrir en edición las tareas recién creadas
+/// * _`strict_transitions:`_ Solo permitir los cambios de estado de [crate::task::STRICT_TRANSITIONS]
+/// * _`history_log:`_ Registrar las altas, ediciones y eliminaciones en "history.log"
+/// * _`stale_after_days:`_ Días sin cambios tras los que una tarea sin terminar se marca "sin actividad"; 0 lo desactiva
+/// * _`date_time_format:`_ Formato de chrono para las fechas; si no es válido se usa el predeterminado
+/// * _`notifications`, `notify_interval_minutes`:_ Avisos del sistema para tareas vencidas o que vencen hoy,
+///   y cada cuántos minutos se revisan
+/// * _`filter_text`, `filter_status`, `sort`:_ Última búsqueda, filtro por estado y orden usados
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Preferences {
+    #[serde(default)]
+    pub theme: Option<ThemeMode>,
+    #[serde(default)]
+    pub warn_duplicates: bool,
+    #[serde(default, deserialize_with = "or_default")]
+    pub view_mode: ViewMode,
+    #[serde(default, deserialize_with = "or_default")]
+    pub status_labels: StatusLabels,
+    #[serde(default)]
+    pub create_in_modal: bool,
+    #[serde(default)]
+    pub auto_in_progress: bool,
+    #[serde(default)]
+    pub edit_new_tasks: bool,
+    #[serde(default)]
+    pub strict_transitions: bool,
+    #[serde(default)]
+    pub history_log: bool,
+    #[serde(default = "default_stale_after_days")]
+    pub stale_after_days: u32,
+    #[serde(default = "default_date_time_format")]
+    pub date_time_format: String,
+    #[serde(default)]
+    pub notifications: bool,
+    #[serde(default = "default_notify_interval")]
+    pub notify_interval_minutes: u64,
+    #[serde(default)]
+    pub filter_text: String,
+    /// Si el estado guardado no es válido, o no tiene ninguno activo, se muestran todas
+    #[serde(default, deserialize_with = "valid_status_filter")]
+    pub filter_status: StatusFilter,
+    #[serde(default, deserialize_with = "or_default")]
+    pub sort: SortBy,
+}
+
+fn default_notify_interval() -> u64 {
+    5
+}
+
+fn default_stale_after_days() -> u32 {
+    30
+}
+
+fn default_date_time_format() -> String {
+    DEFAULT_DATE_TIME_FORMAT.to_string()
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            theme: None,
+            warn_duplicates: false,
+            view_mode: ViewMode::default(),
+            status_labels: StatusLabels::default(),
+            create_in_modal: false,
+            auto_in_progress: false,
+            edit_new_tasks: false,
+            strict_transitions: false,
+            history_log: false,
+            stale_after_days: default_stale_after_days(),
+            date_time_format: default_date_time_format(),
+            notifications: false,
+            notify_interval_minutes: default_notify_interval(),
+            filter_text: String::new(),
+            filter_status: StatusFilter::default(),
+            sort: SortBy::default()
+        }
+    }
+}
+
+/// Deserializa un valor y, si no es válido, usa el valor por defecto en lugar de fallar
+fn or_default<'de, D: Deserializer<'de>, T: DeserializeOwned + Default>(deserializer: D) -> Result<T, D::Error> {
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(T::deserialize(value).unwrap_or_default())
+}
+
+fn valid_status_filter<'de, D: Deserializer<'de>>(deserializer: D) -> Result<StatusFilter, D::Error> {
+    let filter: StatusFilter = or_default(deserializer)?;
+    Ok(filter.or_all())
+}
+
+fn valid_window<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<WindowState>, D::Error> {
+    let window: Option<WindowState> = or_default(deserializer)?;
+    Ok(window.filter(WindowState::is_valid))
+}
+
+impl WindowState {
+    /// Descarta tamaños no positivos y coordenadas que no son finitas
+    pub fn is_valid(&self) -> bool {
+        [self.width, self.height].iter().all(|v| v.is_finite() && *v > 0.0)
+            && [self.x, self.y].iter().flatten().all(|v| v.is_finite())
+    }
+}
+
+/// Obtiene la ruta de "settings.json", ubicado junto al archivo de tareas.
+pub fn settings_path(tasks_path: &Path) -> PathBuf {
+    tasks_path.with_file_name(SETTINGS_FILE)
+}
+
+/// Lee la configuración. Si el archivo no existe se importan los archivos de versiones anteriores, y si no es válido
+/// retorna los valores por defecto.
+pub fn load_settings(path: &Path) -> Settings {
+    match fs::read_to_string(path) {
+        Ok(settings) => serde_json::from_str(&settings).unwrap_or_default(),
+        Err(_) => load_legacy(path)
+    }
+}
+
+/// Guarda la configuración
+pub fn save_settings(path: &Path, settings: &Settings) -> io::Result<()> {
+    write_atomic(path, &serde_json::to_string_pretty(settings)?)
+}
+
+fn load_legacy(path: &Path) -> Settings {
+    let read = |file| fs::read_to_string(path.with_file_name(file)).ok();
+
+    Settings {
+        preferences: read(LEGACY_PREFERENCES_FILE)
+            .and_then(|preferences| serde_json::from_str(&preferences).ok())
+            .unwrap_or_default(),
+        window: read(LEGACY_WINDOW_FILE)
+            .and_then(|window| serde_json::from_str::<WindowState>(&window).ok())
+            .filter(WindowState::is_valid)
+    }
+}
@@ -1,11 +1,12 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr, time::Duration};
 
-use chrono::{Local, NaiveDateTime};
-use iced::{widget::{button, column, combo_box, container, horizontal_space, row, scrollable, text, text_editor, text_input}, Background, Element, Length, Theme};
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc};
+use iced::{alignment::Vertical, widget::{button, checkbox, column, combo_box, container, horizontal_space, mouse_area, row, scrollable, text, text_editor, text_input, tooltip, rich_text, span, text::Span}, font, Background, Element, Font, Length, Theme};
+use iced_aw::{date_picker::Date, helpers::{date_picker, time_picker}, time_picker::Time, Wrap};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::utils::format_date_time;
+use crate::utils::{clamp_text, contains_ignore_case, duration_seconds, format_date_time_as, format_duration, format_minutes, format_relative, format_timestamp_as, locale, match_score, normalize_description, parse_emphasis, parse_tags, split_matches, task_to_clipboard_text, text_stats, truncate_chars, utc_timestamp, utc_to_local};
 
 /// Representa un tarea almacenada.
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
@@ -14,15 +15,77 @@ pub struct Task {
     pub title: String,
     pub description: String,
     pub status: Status,
+    /// Los instantes de creación, modificación y término se guardan en UTC,
+    /// y solo se convierten a la hora local al mostrarlos
+    #[serde(with = "utc_timestamp")]
     created_at: NaiveDateTime,
+    #[serde(with = "utc_timestamp")]
     pub modified_at: NaiveDateTime,
+    /// Fecha límite opcional de la tarea
+    #[serde(default)]
+    pub due_date: Option<NaiveDateTime>,
+    /// Prioridad de la tarea
+    #[serde(default)]
+    pub priority: Priority,
+    /// Etiquetas libres para categorizar la tarea
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Momento en que la tarea pasó a [Status::Done]
+    #[serde(default, with = "utc_timestamp::option")]
+    completed_at: Option<NaiveDateTime>,
+    /// Posición elegida por el usuario en el orden manual
+    #[serde(default)]
+    pub order: u32,
+    /// Lista de pasos de la tarea
+    #[serde(default)]
+    pub subtasks: Vec<Subtask>,
+    /// Las tareas archivadas no aparecen en la lista normal
+    #[serde(default)]
+    pub archived: bool,
+    /// Color de acento elegido por el usuario, en RGB
+    #[serde(default)]
+    pub color: Option<[u8; 3]>,
+    /// Momento en que la tarea entró a [Status::InProgress], si sigue en ese estado
+    #[serde(default, with = "utc_timestamp::option")]
+    in_progress_since: Option<NaiveDateTime>,
+    /// Tiempo acumulado en [Status::InProgress] en periodos anteriores, guardado en segundos
+    #[serde(default, with = "duration_seconds")]
+    total_in_progress: Duration,
+    /// Tareas que deben terminarse antes que esta
+    #[serde(default)]
+    pub depends_on: Vec<Uuid>,
+    /// Tiempo estimado para terminar la tarea, en minutos
+    #[serde(default)]
+    pub estimate_minutes: Option<u32>,
+    /// Última vez que se abrió la tarea para editarla o se expandió, en UTC
+    #[serde(default, with = "utc_timestamp::option")]
+    pub last_viewed_at: Option<NaiveDateTime>,
+    /// Las tareas favoritas aparecen antes que las demás, sin importar el orden elegido
+    #[serde(default)]
+    pub favorite: bool,
+}
+
+/// Largo máximo del título; los títulos más largos rompen el diseño de la lista
+pub const MAX_TITLE_CHARS: usize = 120;
+
+/// Quita los espacios al inicio y al final del título y lo recorta a [MAX_TITLE_CHARS]
+fn normalize_title(title: &str) -> String {
+    truncate_chars(title.trim(), MAX_TITLE_CHARS)
+}
+
+/// Paso dentro de una [Task]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Subtask {
+    pub title: String,
+    pub done: bool,
 }
 impl Task {
     /// Crea una nueva intancia de [Task] a partir de un titulo y una descripción.
+    /// Ambos se limpian igual que en [Task::set_title] y [Task::set_description].
     pub fn new<T: AsRef<str>>(title: T, description: T) -> Self {
-        let now = Local::now().naive_local();
-        let title = title.as_ref().to_string();
-        let description = description.as_ref().to_string();
+        let now = Utc::now().naive_utc();
+        let title = normalize_title(title.as_ref());
+        let description = normalize_description(description.as_ref());
 
         Task {
             id: Uuid::new_v4(),
@@ -31,27 +94,186 @@ impl Task {
             status: Status::ToDo,
             created_at: now,
             modified_at: now,
+            due_date: None,
+            priority: Priority::default(),
+            tags: Vec::new(),
+            completed_at: None,
+            order: 0,
+            subtasks: Vec::new(),
+            archived: false,
+            color: None,
+            in_progress_since: None,
+            total_in_progress: Duration::ZERO,
+            depends_on: Vec::new(),
+            estimate_minutes: None,
+            last_viewed_at: None,
+            favorite: false,
+        }
+    }
+
+    /// Reconstruye una tarea existente a partir de todos sus campos (p. ej. al importar)
+    pub fn restore(id: Uuid, title: String, description: String, status: Status, created_at: NaiveDateTime, modified_at: NaiveDateTime) -> Self {
+        Task {
+            id,
+            title,
+            description,
+            status,
+            created_at,
+            modified_at,
+            ..Task::new("", "")
         }
     }
 
+    /// Cambia el título, sin espacios al inicio ni al final y recortado a [MAX_TITLE_CHARS]
     pub fn set_title(&mut self, title: String) {
-        self.title = title;
+        self.title = normalize_title(&title);
     }
+    /// Cambia la descripción, limpiando los espacios y las líneas en blanco repetidas, ver [normalize_description]
     pub fn set_description(&mut self, description: String) {
-        self.description = description;
+        self.description = normalize_description(&description);
     }
+    /// Cambia el estado, registrando o limpiando `completed_at` al entrar o salir de [Status::Done]
+    /// y acumulando el tiempo que la tarea pasa en [Status::InProgress]
     pub fn set_status(&mut self, status: Status) {
+        self.set_status_at(status, Utc::now().naive_utc());
+    }
+
+    /// Igual que [Task::set_status], pero si `strict` solo permite los cambios de [STRICT_TRANSITIONS]
+    pub fn try_set_status(&mut self, status: Status, strict: bool) -> Result<(), TransitionError> {
+        self.status.check_transition(status, strict)?;
+        self.set_status(status);
+        Ok(())
+    }
+
+    /// Igual que [Task::set_status], tomando `now` (en UTC) como el momento del cambio
+    pub fn set_status_at(&mut self, status: Status, now: NaiveDateTime) {
+        if status == Status::Done && self.status != Status::Done {
+            self.completed_at = Some(now);
+        }else if status != Status::Done {
+            self.completed_at = None;
+        }
+
+        if status == Status::InProgress && self.status != Status::InProgress {
+            self.in_progress_since = Some(now);
+        }else if status != Status::InProgress {
+            if let Some(since) = self.in_progress_since.take() {
+                self.total_in_progress += (now - since).to_std().unwrap_or_default();
+            }
+        }
+
         self.status = status;
     }
+    pub fn set_due_date(&mut self, due_date: Option<NaiveDateTime>) {
+        self.due_date = due_date;
+    }
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+    }
+    pub fn set_color(&mut self, color: Option<[u8; 3]>) {
+        self.color = color;
+    }
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags = Vec::new();
+        tags.iter().for_each(|tag| self.add_tag(tag));
+    }
+
+    /// Agrega una etiqueta si no está vacía ni repetida
+    pub fn add_tag(&mut self, tag: &str) {
+        let tag = tag.trim();
+        if !tag.is_empty() && !self.tags.iter().any(|t| t == tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) {
+        self.tags.retain(|t| t != tag.trim());
+    }
+
+    /// Indica si la tarea tiene la etiqueta `tag` (sin distinguir mayúsculas)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.to_lowercase() == tag.trim().to_lowercase())
+    }
+
+    /// Agrega un paso pendiente al final de la lista
+    pub fn add_subtask(&mut self, title: &str) {
+        let title = title.trim();
+        if !title.is_empty() {
+            self.subtasks.push(Subtask { title: title.to_string(), done: false });
+        }
+    }
+
+    pub fn remove_subtask(&mut self, index: usize) {
+        if index < self.subtasks.len() {
+            self.subtasks.remove(index);
+        }
+    }
+
+    /// Marca o desmarca el paso en `index`
+    pub fn toggle_subtask(&mut self, index: usize) {
+        if let Some(subtask) = self.subtasks.get_mut(index) {
+            subtask.done = !subtask.done;
+        }
+    }
+
+    /// Cantidad de pasos completados y total de pasos
+    pub fn subtask_progress(&self) -> (usize, usize) {
+        (self.subtasks.iter().filter(|subtask| subtask.done).count(), self.subtasks.len())
+    }
+
+    /// Título tal como se busca y se resalta, sin espacios al inicio ni al final
+    pub fn searchable_title(&self) -> &str {
+        self.title.trim()
+    }
+
+    /// Descripción tal como se busca y se resalta. Las tareas importadas o leídas de un archivo editado a mano
+    /// pueden traer espacios alrededor que no pasaron por [Task::set_description].
+    pub fn searchable_description(&self) -> &str {
+        self.description.trim()
+    }
+
+    /// Indica si el titulo, la descripción o alguna etiqueta contienen `query` (sin distinguir mayúsculas)
+    pub fn matches(&self, query: &str) -> bool {
+        contains_ignore_case(self.searchable_title(), query)
+            || contains_ignore_case(self.searchable_description(), query)
+            || self.tags.iter().any(|tag| contains_ignore_case(tag, query))
+    }
+
+    /// Puntaje de búsqueda aproximada, ver [match_score].
+    /// Las coincidencias en el titulo pesan más que en las etiquetas, y estas más que en la descripción.
+    pub fn score(&self, query: &str) -> Option<u32> {
+        [
+            match_score(self.searchable_title(), query).map(|score| score + 2),
+            self.tags.iter().filter_map(|tag| match_score(tag, query)).max().map(|score| score + 1),
+            match_score(self.searchable_description(), query)
+        ].into_iter().flatten().max()
+    }
+
+    /// Indica si la fecha límite ya pasó y la tarea no está terminada
+    pub fn is_overdue(&self) -> bool {
+        match self.due_date {
+            Some(due_date) => self.status != Status::Done && due_date < Local::now().naive_local(),
+            None => false
+        }
+    }
+
+    /// Registra que la tarea se acaba de abrir, ver [Task::last_viewed_at]
+    pub fn mark_viewed(&mut self) {
+        self.last_viewed_at = Some(Utc::now().naive_utc());
+    }
 
     pub fn modified(&mut self) {
-        self.modified_at = Local::now().naive_local()
+        self.modified_at = Utc::now().naive_utc()
     }
 
     pub fn id(&self) -> Uuid {
         self.id
     }
 
+    /// Asigna un nuevo identificador aleatorio a la tarea
+    pub fn regenerate_id(&mut self) {
+        self.id = Uuid::new_v4();
+    }
+
     pub fn created_at(&self) -> NaiveDateTime {
         self.created_at
     }
@@ -60,26 +282,98 @@ impl Task {
         self.modified_at
     }
 
+    /// Si la tarea se creó o vence el día `day`, en hora local
+    pub fn falls_on(&self, day: NaiveDate) -> bool {
+        utc_to_local(self.created_at).date() == day || self.due_date.is_some_and(|due_date| due_date.date() == day)
+    }
+
+    /// Si la tarea se creó entre `start` y `end` inclusive, en hora local. Un límite en [None] no restringe ese lado.
+    pub fn created_between(&self, start: Option<NaiveDateTime>, end: Option<NaiveDateTime>) -> bool {
+        let created_at = utc_to_local(self.created_at);
+        start.is_none_or(|start| created_at >= start) && end.is_none_or(|end| created_at <= end)
+    }
+
+    /// Si alguno de los campos es distinto de `other`, sin contar la fecha de modificación.
+    /// Aceptar una edición sin cambios actualiza esa fecha pero no cambia nada más.
+    pub fn differs_from(&self, other: &Task) -> bool {
+        *self != Task { modified_at: self.modified_at, ..other.clone() }
+    }
+
+    /// Si la tarea se modificó después de crearse
+    pub fn is_edited(&self) -> bool {
+        self.created_at != self.modified_at
+    }
+
+    /// Si la tarea no está terminada y no se modifica desde hace más de `older_than`
+    pub fn is_stale(&self, older_than: Duration) -> bool {
+        self.is_stale_at(Utc::now().naive_utc(), older_than)
+    }
+
+    /// Igual que [Task::is_stale], tomando `now` (en UTC) como el momento actual
+    pub fn is_stale_at(&self, now: NaiveDateTime, older_than: Duration) -> bool {
+        self.status != Status::Done && TimeDelta::from_std(older_than).is_ok_and(|limit| now - self.modified_at > limit)
+    }
+
+    pub fn completed_at(&self) -> Option<NaiveDateTime> {
+        self.completed_at
+    }
+
+    /// Momento en que la tarea entró a [Status::InProgress], si sigue en ese estado
+    pub fn in_progress_since(&self) -> Option<NaiveDateTime> {
+        self.in_progress_since
+    }
+
+    /// Tiempo acumulado en [Status::InProgress] sin contar el periodo actual
+    pub fn total_in_progress(&self) -> Duration {
+        self.total_in_progress
+    }
+
+    /// Restaura los datos de seguimiento que normalmente mantiene [Task::set_status]
+    pub fn restore_tracking(&mut self, completed_at: Option<NaiveDateTime>, in_progress_since: Option<NaiveDateTime>, total_in_progress: Duration) {
+        self.completed_at = completed_at;
+        self.in_progress_since = in_progress_since;
+        self.total_in_progress = total_in_progress;
+    }
+
+    /// Tiempo total en [Status::InProgress] hasta `now`, incluido el periodo actual si sigue en progreso
+    pub fn time_in_progress(&self, now: NaiveDateTime) -> Duration {
+        let current = self.in_progress_since
+            .and_then(|since| (now - since).to_std().ok())
+            .unwrap_or_default();
+
+        self.total_in_progress + current
+    }
+
+    /// Igual que [Task::modify], pero si el cambio de estado no está permitido no modifica nada, ver [Task::try_set_status]
+    pub fn try_modify(&mut self, title: Option<String>, description: Option<String>, status: Option<Status>, strict: bool) -> Result<(), TransitionError> {
+        if let Some(status) = status {
+            self.status.check_transition(status, strict)?;
+        }
+        self.modify(title, description, status);
+        Ok(())
+    }
+
     /// Edita esta instancia de [Task] 
     pub fn modify(&mut self, title: Option<String>, description: Option<String>, status: Option<Status>)  {
         if let Some(title) = title {
-            self.title = title
+            self.set_title(title)
         }
 
         if let Some(description) = description {
-            self.description = description
+            self.set_description(description)
         }
 
         if let Some(status) = status {
-            self.status = status
+            self.set_status(status)
         }
 
-        self.modified_at = Local::now().naive_local();
+        self.modified_at = Utc::now().naive_utc();
     }
 }
 
-/// Representa el estado de una instacia de [Task]
-#[derive(Deserialize, Serialize, PartialEq, Clone, Debug, Copy)]
+/// Representa el estado de una instacia de [Task].
+/// Se ordena según el flujo de trabajo, ver [Status::rank]; el orden de las variantes no importa.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Hash, Clone, Debug, Copy)]
 #[serde(rename_all = "kebab-case")]
 pub enum Status {
     Done,
@@ -87,23 +381,93 @@ pub enum Status {
     ToDo
 }
 
+/// Representa la prioridad de una instancia de [Task]
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High
+}
+
 /// Represeta el estado local de una instancia de [Task].
 /// * _`state:`_ Indíca si la tarea está en vista estática o en edición
 /// * _`fields:`_ Ayuda a manejar la lógica de estado y pintado de la instancia 
+/// * _`pending_delete:`_ Indica si se pidió eliminar la tarea y falta confirmar
+/// * _`expanded:`_ Indica si la descripción se muestra completa
+/// * _`focused:`_ Indica si es la tarea seleccionada para los atajos de teclado
 #[derive(Debug)]
 pub struct TaskView {
     task: Task,
     state: State,
 
-    fields: Field
+    fields: Field,
+    pending_delete: bool,
+    expanded: bool,
+    focused: bool,
+    /// Al abrir la edición de una tarea pendiente, proponer [Status::InProgress]
+    auto_in_progress: bool,
+    /// Rechazar los cambios de estado fuera de [STRICT_TRANSITIONS]
+    strict_transitions: bool
 }
 
+/// Líneas y caracteres de la descripción que se muestran antes de "Mostrar más"
+const DESCRIPTION_LINES: usize = 3;
+const DESCRIPTION_CHARS: usize = 180;
+
+/// Largo recomendado de la descripción; pasado este límite el contador se pinta en rojo
+pub const DESCRIPTION_MAX_CHARS: usize = 1000;
+
+/// Alto del editor de descripción: crece con el texto entre estos límites y pasado el máximo se desplaza por dentro
+pub const EDITOR_MIN_HEIGHT: f32 = 75.0;
+pub const EDITOR_MAX_HEIGHT: f32 = 200.0;
+/// Alto aproximado de una línea con el tamaño de texto por defecto, más el relleno del editor
+const EDITOR_LINE_HEIGHT: f32 = 21.0;
+const EDITOR_PADDING: f32 = 10.0;
+
 #[derive(Debug)]
 pub struct Field {
     title: String,
     status: Status,
-    combo_state: combo_box::State<Status>,
-    text_editor_content: text_editor::Content
+    status_options: Vec<StatusOption>,
+    combo_state: combo_box::State<StatusOption>,
+    priority: Priority,
+    priority_combo_state: combo_box::State<Priority>,
+    tags: String,
+    subtasks: Vec<Subtask>,
+    new_subtask: String,
+    depends_on: Vec<Uuid>,
+    /// Tareas que se pueden elegir como dependencia, las asigna [crate::task_tracker::TaskTracker]
+    dependency_options: Vec<DependencyOption>,
+    dependency_combo_state: combo_box::State<DependencyOption>,
+    text_editor_content: text_editor::Content,
+    due_date: Option<NaiveDateTime>,
+    picker: Option<Picker>,
+    color: Option<[u8; 3]>,
+    /// Minutos estimados tal como se escriben, solo dígitos
+    estimate: String,
+    /// Muestra la vista previa de la descripción en lugar del editor
+    preview: bool,
+    /// Motivo por el que se rechazó el último cambio de estado
+    transition_error: Option<TransitionError>
+}
+
+/// Colores de acento que se pueden elegir en la vista de edición
+pub const COLORS: &[[u8; 3]] = &[
+    [231, 76, 60],
+    [230, 126, 34],
+    [241, 196, 15],
+    [46, 204, 113],
+    [52, 152, 219],
+    [155, 89, 182],
+];
+
+/// Selector de fecha límite abierto en la vista de edición
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Picker {
+    Date,
+    Time
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -113,35 +477,171 @@ pub enum State {
     Edit
 }
 
+/// Forma de pintar las tarjetas en la lista
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ViewMode {
+    /// Tarjeta completa con descripción, fechas y acciones
+    #[default]
+    Normal,
+    /// Una sola fila con el título, el estado y las acciones principales
+    Compact
+}
+
 #[derive(Debug, Clone)]
 pub enum Message {
     /// Modificar la instancia actual de [Task]
     Modify {
         title: Option<String>,
         description: Option<String>,
-        status: Option<Status>
+        status: Option<Status>,
+        due_date: Option<Option<NaiveDateTime>>,
+        priority: Option<Priority>,
+        tags: Option<Vec<String>>,
+        subtasks: Option<Vec<Subtask>>,
+        color: Option<Option<[u8; 3]>>,
+        depends_on: Option<Vec<Uuid>>,
+        estimate_minutes: Option<Option<u32>>
     },
 
     // Manejo de estado y pintado
     SetTitle(String),
     SetDescription(text_editor::Action),
     SetStatus(Status),
+    SetPriority(Priority),
+    SetColor(Option<[u8; 3]>),
+    SetEstimate(String),
+    AddDependency(Uuid),
+    RemoveDependency(usize),
+    SetTags(String),
+    SetNewSubtask(String),
+    AddSubtask,
+    RemoveSubtask(usize),
+    /// Marca o desmarca un paso desde la vista estática
+    ToggleSubtask(usize),
+    SetDueDate(Date),
+    SetDueTime(Time),
+    ClearDueDate,
+    /// Abre o cierra los selectores de fecha y hora
+    SetPicker(Option<Picker>),
 
     /// Intercambia de vista estática a edición
     ToggleState,
+    /// Salir de la vista de edición descartando los cambios
+    CancelEdit,
+    /// Muestra u oculta la descripción completa
+    ToggleExpanded,
+    /// Alterna entre el editor de la descripción y su vista previa
+    TogglePreview,
+    /// Se hizo click en la tarjeta, que pasa a ser la tarea seleccionada
+    Focus,
     /// Se ha actualizado la instancia de [Task]
     Update,
 
+    /// Primer click en "Eliminar", pide confirmación
+    RequestDelete,
+    /// Segundo click, confirma la eliminación
+    ConfirmDelete,
+    /// Descarta la eliminación pendiente
+    CancelDelete,
+
+    /// Mover la tarea una posición hacia arriba o abajo en el orden manual
+    MoveUp,
+    MoveDown,
+
     /// Notificar que se ha eliminado una instancia de [Task]
     Delete(Uuid),
+    /// Solicitar una copia de esta instancia de [Task]
+    Duplicate(Uuid),
+    /// Solicitar que se copie este texto al portapapeles, ver [task_to_clipboard_text]
+    CopyToClipboard(String),
+    /// Solicitar que se archive o desarchive esta instancia de [Task]
+    ToggleArchive(Uuid),
+    /// Solicitar que se marque o desmarque como favorita esta instancia de [Task]
+    ToggleFavorite(Uuid),
+    /// Solicitar que la lista se filtre por una etiqueta
+    FilterTag(String),
 }
 impl Status {
     pub const ALL: &'static [Self] = &[Status::Done, Status::InProgress, Status::ToDo];
+
+    /// Posición en el flujo de trabajo: [Status::ToDo] < [Status::InProgress] < [Status::Done]
+    pub fn rank(&self) -> u8 {
+        match self {
+            Status::ToDo => 0,
+            Status::InProgress => 1,
+            Status::Done => 2
+        }
+    }
+}
+
+impl Ord for Status {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl PartialOrd for Status {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    /// Acepta tanto el formato de serialización ("to-do") como la etiqueta mostrada ("Pendiente")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "done" | "terminada" => Ok(Status::Done),
+            "in-progress" | "en progreso" => Ok(Status::InProgress),
+            "to-do" | "pendiente" => Ok(Status::ToDo),
+            other => Err(format!("Estado desconocido: \"{}\"", other))
+        }
+    }
+}
+
+/// Cambios de estado permitidos con [crate::settings::Preferences::strict_transitions]: avanzar un paso
+/// a la vez, o volver un paso atrás. Quedarse en el mismo estado siempre está permitido.
+pub const STRICT_TRANSITIONS: &[(Status, Status)] = &[
+    (Status::ToDo, Status::InProgress),
+    (Status::InProgress, Status::Done),
+    (Status::InProgress, Status::ToDo),
+    (Status::Done, Status::InProgress),
+];
+
+/// Cambio de estado rechazado por el modo estricto
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransitionError {
+    pub from: Status,
+    pub to: Status
+}
+
+impl Display for TransitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let next = STRICT_TRANSITIONS.iter()
+            .filter(|(from, _)| *from == self.from)
+            .map(|(_, to)| format!("\"{}\"", to))
+            .collect::<Vec<_>>()
+            .join(" o ");
+        write!(f, "No se puede pasar de \"{}\" a \"{}\"; desde \"{}\" solo se puede pasar a {}", self.from, self.to, self.from, next)
+    }
+}
+
+impl Status {
+    /// Indica si se puede pasar de este estado a `to`. Si no es `strict` cualquier cambio está permitido.
+    pub fn check_transition(self, to: Status, strict: bool) -> Result<(), TransitionError> {
+        if !strict || self == to || STRICT_TRANSITIONS.contains(&(self, to)) {
+            Ok(())
+        }else {
+            Err(TransitionError { from: self, to })
+        }
+    }
 }
 
 impl Display for Status {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}", match self {
+        write!(f, "{}", match self {
             Status::Done => "Terminada",
             Status::InProgress => "En progreso",
             Status::ToDo => "Pendiente"
@@ -149,6 +649,20 @@ impl Display for Status {
     }
 }
 
+impl Priority {
+    pub const ALL: &'static [Self] = &[Priority::High, Priority::Medium, Priority::Low];
+}
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Priority::High => "Alta",
+            Priority::Medium => "Media",
+            Priority::Low => "Baja"
+        })
+    }
+}
+
 impl TaskView {
     /// Obtiene una referencia a la instancia de [Task] que pinta la istancia actual de [TaskView]
     pub fn get_task(&self) -> &Task {
@@ -160,85 +674,452 @@ impl TaskView {
         &mut self.task
     }
 
+    /// Indica si la tarea está en vista de edición
+    pub fn is_editing(&self) -> bool {
+        self.state == State::Edit
+    }
+
+    /// Indica si la descripción se muestra completa
+    pub fn is_expanded(&self) -> bool {
+        self.expanded
+    }
+
+    /// Asigna las tareas que se pueden elegir como dependencia en la vista de edición
+    pub fn set_dependency_options(&mut self, options: Vec<DependencyOption>) {
+        self.fields.dependency_options = options;
+        self.refresh_dependency_combo();
+    }
+
+    /// Usa los nombres y el orden de `labels` en el selector de estado
+    pub fn set_status_labels(&mut self, labels: &StatusLabels) {
+        self.fields.status_options = status_options(labels);
+        self.fields.combo_state = combo_box::State::new(self.fields.status_options.clone());
+    }
+
+    /// Reconstruye el selector de dependencias sin las que ya están elegidas
+    fn refresh_dependency_combo(&mut self) {
+        let available = self.fields.dependency_options.iter()
+            .filter(|option| !self.fields.depends_on.contains(&option.id))
+            .cloned()
+            .collect();
+        self.fields.dependency_combo_state = combo_box::State::new(available);
+    }
+
+    /// Muestra la descripción completa o recortada
+    pub fn set_expanded(&mut self, expanded: bool) {
+        self.expanded = expanded;
+    }
+
+    /// Indica si la tarea espera confirmación para eliminarse
+    pub fn is_pending_delete(&self) -> bool {
+        self.pending_delete
+    }
+
+    /// Valores actuales de los campos de edición
+    pub fn fields(&self) -> &Field {
+        &self.fields
+    }
+
+    /// Descarta los cambios sin guardar, volviendo a cargar los campos desde la tarea.
+    /// El editor de descripción se conserva, con su cursor y desplazamiento, mientras su texto
+    /// coincida con el de la tarea.
+    pub fn reset_fields(&mut self) {
+        let mut fields = Field::from(&self.task);
+        if fields.text_editor_content.text() == self.fields.text_editor_content.text() {
+            std::mem::swap(&mut fields.text_editor_content, &mut self.fields.text_editor_content);
+        }
+        self.fields = fields;
+    }
+
+    /// Indica si la tarea es la seleccionada
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Marca o desmarca la tarea como seleccionada
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    /// Activa o desactiva el modo estricto de cambios de estado, ver [STRICT_TRANSITIONS]
+    pub fn set_strict_transitions(&mut self, strict: bool) {
+        self.strict_transitions = strict;
+    }
+
+    /// Activa o desactiva el cambio automático a [Status::InProgress] al entrar en edición
+    pub fn set_auto_in_progress(&mut self, auto_in_progress: bool) {
+        self.auto_in_progress = auto_in_progress;
+    }
+
     /// Lógica de actualización de estado
     pub fn update(&mut self,  message: Message) -> iced::Task<Message> {
         match message {
             // Modificar esta tarea
-            Message::Modify { title, description, status } => {
+            Message::Modify { title, description, status, due_date, priority, tags, subtasks, color, depends_on, estimate_minutes } => {
+                // Un cambio de estado no permitido rechaza la edición completa y la deja abierta
+                if let Some(Err(error)) = status.map(|status| self.task.status.check_transition(status, self.strict_transitions)) {
+                    self.fields.transition_error = Some(error);
+                    return iced::Task::none()
+                }
+                if let Some(estimate_minutes) = estimate_minutes {
+                    self.task.estimate_minutes = estimate_minutes;
+                }
+                if let Some(depends_on) = depends_on {
+                    self.task.depends_on = depends_on;
+                }
+                if let Some(color) = color {
+                    self.task.set_color(color);
+                }
+                if let Some(subtasks) = subtasks {
+                    self.task.subtasks = subtasks;
+                }
+                if let Some(tags) = tags {
+                    self.task.set_tags(tags);
+                }
+                if let Some(due_date) = due_date {
+                    self.task.set_due_date(due_date);
+                }
+                if let Some(priority) = priority {
+                    self.task.set_priority(priority);
+                }
                 self.task.modify(title, description, status);
 
                 return iced::Task::done(Message::ToggleState).chain(iced::Task::done(Message::Update))
             },
 
             // Actualización deestado
-            Message::SetTitle(title) => self.fields.title = title,
+            Message::SetTitle(title) => self.fields.title = title.chars().take(MAX_TITLE_CHARS).collect(),
             Message::SetDescription(action) => self.fields.text_editor_content.perform(action),
-            Message::SetStatus(status) => self.fields.status = status,
+            Message::SetStatus(status) => {
+                self.fields.status = status;
+                self.fields.transition_error = None;
+            }
+            Message::SetPriority(priority) => self.fields.priority = priority,
+            Message::SetColor(color) => self.fields.color = color,
+            // Solo se aceptan números
+            Message::SetEstimate(estimate) if estimate.chars().all(|c| c.is_ascii_digit()) && estimate.len() <= 6 => {
+                self.fields.estimate = estimate
+            }
+            Message::AddDependency(id) if !self.fields.depends_on.contains(&id) => {
+                self.fields.depends_on.push(id);
+                self.refresh_dependency_combo();
+            }
+            Message::RemoveDependency(index) if index < self.fields.depends_on.len() => {
+                self.fields.depends_on.remove(index);
+                self.refresh_dependency_combo();
+            }
+            Message::SetTags(tags) => self.fields.tags = tags,
+            Message::SetNewSubtask(title) => self.fields.new_subtask = title,
+            Message::AddSubtask => {
+                let title = self.fields.new_subtask.trim();
+                if !title.is_empty() {
+                    self.fields.subtasks.push(Subtask { title: title.to_string(), done: false });
+                    self.fields.new_subtask.clear();
+                }
+            },
+            Message::RemoveSubtask(index) if index < self.fields.subtasks.len() => {
+                self.fields.subtasks.remove(index);
+            },
+            Message::ToggleSubtask(index) => {
+                self.task.toggle_subtask(index);
+                self.task.modified();
+                self.fields.subtasks = self.task.subtasks.clone();
+
+                return iced::Task::done(Message::Update)
+            },
+            Message::SetDueDate(date) => {
+                let time = self.fields.due_date.map(|due_date| due_date.time()).unwrap_or_default();
+                self.fields.due_date = Some(NaiveDate::from(date).and_time(time));
+                self.fields.picker = None;
+            },
+            Message::SetDueTime(time) => {
+                let date = self.fields.due_date.map(|due_date| due_date.date()).unwrap_or(Local::now().date_naive());
+                self.fields.due_date = Some(date.and_time(NaiveTime::from(time)));
+                self.fields.picker = None;
+            },
+            Message::ClearDueDate => self.fields.due_date = None,
+            Message::TogglePreview => self.fields.preview = !self.fields.preview,
+            Message::SetPicker(picker) => self.fields.picker = picker,
+            Message::ToggleExpanded => {
+                self.expanded = !self.expanded;
+                if self.expanded {
+                    self.task.mark_viewed();
+                }
+            }
+            Message::RequestDelete => self.pending_delete = true,
+            Message::CancelDelete => self.pending_delete = false,
+            Message::Focus => {
+                self.focused = true;
+                self.pending_delete = false;
+            }
+            Message::ConfirmDelete => {
+                self.pending_delete = false;
+                return iced::Task::done(Message::Delete(self.task.id))
+            },
+            Message::CancelEdit => {
+                self.reset_fields();
+                self.state = State::Static;
+            }
             Message::ToggleState => match self.state {
                 State::Edit => self.state = State::Static,
-                State::Static => self.state = State::Edit
+                State::Static => {
+                    // Solo cambia el valor del formulario, la tarea no se guarda hasta aceptar
+                    if self.auto_in_progress && self.task.status == Status::ToDo {
+                        self.fields.status = Status::InProgress;
+                    }
+                    self.task.mark_viewed();
+                    self.state = State::Edit
+                }
             },
 
             _ => ()
             // Estos mensajes son para el estado global
             // Message::Delete(id)
+            // Message::FilterTag(tag)
+            // Message::Duplicate(id)
+            // Message::ToggleArchive(id)
+            // Message::ToggleFavorite(id)
+            // Message::MoveUp | Message::MoveDown
             // Message::Update
         }
 
         iced::Task::none()
     }
 
-    /// Lógica de pintado
-    pub fn view(&self) -> iced::Element<Message> {
-        container(match self.state {
-            State::Static => self.static_view(),
-            State::Edit => self.edit_view()
+    /// Lógica de pintado. Las coincidencias de `query` se resaltan en el título y la descripción,
+    /// si `blocked` se indica que alguna dependencia no está terminada y si `stale` que la tarea
+    /// lleva tiempo sin cambios. Las fechas se muestran con `date_format`, ver [format_date_time_as].
+    pub fn view(&self, query: &str, blocked: bool, stale: bool, mode: ViewMode, labels: &StatusLabels, date_format: &str) -> iced::Element<'_, Message> {
+        // La edición siempre usa el formulario completo, sin importar el modo
+        let card = container(match (&self.state, mode) {
+            (State::Static, ViewMode::Normal) => self.static_view(query, blocked, stale, labels, date_format),
+            (State::Static, ViewMode::Compact) => self.compact_view(query, blocked, labels),
+            (State::Edit, _) => self.edit_view()
+        })
+        .style(|theme: &Theme| {
+            let style = container::rounded_box(theme);
+
+            // La tarea seleccionada se resalta con un borde
+            if self.focused {
+                style.border(iced::Border {
+                    color: theme.extended_palette().primary.base.color,
+                    width: 2.0,
+                    ..style.border
+                })
+            }else {
+                style
+            }
         })
-        .style(container::rounded_box)
         .height(Length::Shrink)
-        .max_height(300)
+        .max_height(if self.expanded || self.state == State::Edit {f32::INFINITY} else {300.0});
+
+        // Hacer click en cualquier otra parte de la tarjeta la selecciona y cancela la eliminación pendiente
+        mouse_area(card).on_press(Message::Focus).into()
+    }
+
+    /// Estrella para marcar la tarea como favorita, rellena y en color cuando está activa
+    fn favorite_button(&self, size: u16) -> Element<'_, Message> {
+        let favorite = self.task.favorite;
+
+        button(text(if favorite {"★"} else {"☆"}).size(size))
+        .on_press(Message::ToggleFavorite(self.task.id))
+        .style(move |theme: &Theme, status| {
+            let style = button::text(theme, status);
+            if favorite {
+                button::Style { text_color: rgb(FAVORITE_COLOR), ..style }
+            }else {
+                style
+            }
+        })
+        .padding([0, 4])
+        .into()
+    }
+
+    /// Etiqueta con el estado de la tarea
+    fn status_badge(&self, labels: &StatusLabels) -> Element<'_, Message> {
+        container(text(labels.get(self.task.status).singular.clone())).style(|theme: &Theme| {
+            let extended_palette = theme.extended_palette();
+            let style = container::rounded_box(theme);
+
+            let pair = match self.task.status {
+                Status::Done => extended_palette.success.strong,
+                Status::ToDo => extended_palette.danger.strong,
+                Status::InProgress => extended_palette.secondary.weak
+            };
+
+            style.background(Background::Color(pair.color)).color(pair.text)
+        }).padding(5).into()
+    }
+
+    // Vista compacta: una sola fila con el título, el estado y las acciones principales
+    fn compact_view(&self, query: &str, blocked: bool, labels: &StatusLabels) -> Element<'_, Message> {
+        row![]
+        // Punto con el color de acento
+        .push_maybe(self.task.color.map(|color| {
+            container(horizontal_space())
+            .width(8)
+            .height(8)
+            .style(move |_: &Theme| container::Style::default().background(rgb(color)).border(iced::border::rounded(4)))
+        }))
+        .push(self.favorite_button(12))
+        .push(highlighted(self.task.searchable_title(), query))
+        .push(horizontal_space())
+        .push_maybe(blocked.then(|| text("Bloqueada").size(12).style(text::danger)))
+        .push(self.status_badge(labels))
+        .push(button(text("✎").size(12)).on_press(Message::ToggleState).style(button::secondary).padding([2, 6]))
+        .push(
+            button(text(if self.task.archived {"Desarchivar"} else {"Archivar"}).size(12))
+            .on_press(Message::ToggleArchive(self.task.id))
+            .style(button::secondary)
+            .padding([2, 6])
+        )
+        .push_maybe((self.task.archived || self.pending_delete).then(|| if self.pending_delete {
+            button(text("¿Confirmar?").size(12)).on_press(Message::ConfirmDelete).style(button::danger).padding([2, 6])
+        }else {
+            button(text("✕").size(12)).on_press(Message::RequestDelete).style(button::danger).padding([2, 6])
+        }))
+        .push_maybe(self.pending_delete.then(|| {
+            button(text("Cancelar").size(12)).on_press(Message::CancelDelete).style(button::secondary).padding([2, 6])
+        }))
+        .align_y(Vertical::Center)
+        .padding([5, 10])
+        .spacing(8)
         .into()
     }
 
     /// Vista estática
-    fn static_view(&self) -> Element<Message> {
-        column![].push(
+    fn static_view(&self, query: &str, blocked: bool, stale: bool, labels: &StatusLabels, date_format: &str) -> Element<'_, Message> {
+        column![]
+        // Franja con el color de acento
+        .push_maybe(self.task.color.map(|color| {
+            container(horizontal_space())
+            .width(Length::Fill)
+            .height(4)
+            .style(move |_: &Theme| container::Style::default().background(rgb(color)).border(iced::border::rounded(2)))
+        }))
+        .push(
             // Titulo
-            row![].push(text(&self.task.title))
+            row![]
+            .push(self.favorite_button(16))
+            .push(highlighted(self.task.searchable_title(), query))
+
+            // Modificada después de crearse
+            .push_maybe(self.task.is_edited().then(|| container(text("editado").size(12).style(text::secondary)).padding([0, 8])))
+
+            // Sin cambios desde hace tiempo, ver [crate::settings::Preferences::stale_after_days]
+            .push_maybe(stale.then(|| {
+                container(text("sin actividad").size(12)).style(|theme: &Theme| {
+                    let pair = theme.extended_palette().background.weak;
+                    container::rounded_box(theme).background(Background::Color(pair.color)).color(pair.text)
+                }).padding([2, 8])
+            }))
             .push(horizontal_space())
 
-            // Estatus
-            .push(container(text(self.task.status.to_string())).style(|theme: &Theme| {
+            // Bloqueada por dependencias
+            .push_maybe(blocked.then(|| {
+                container(text("Bloqueada")).style(|theme: &Theme| {
+                    let pair = theme.extended_palette().danger.weak;
+                    container::rounded_box(theme).background(Background::Color(pair.color)).color(pair.text)
+                }).padding(5)
+            }))
+
+            // Prioridad
+            .push(container(text(self.task.priority.to_string())).style(|theme: &Theme| {
                 let extended_palette = theme.extended_palette();
                 let style = container::rounded_box(theme);
 
-                style.background(match self.task.status {
-                    Status::Done => Background::Color(extended_palette.success.strong.color),
-                    Status::ToDo => Background::Color(extended_palette.danger.strong.color),
-                    Status::InProgress => Background::Color(extended_palette.secondary.weak.color)
-                })
+                let pair = match self.task.priority {
+                    Priority::High => extended_palette.danger.weak,
+                    Priority::Medium => extended_palette.primary.weak,
+                    Priority::Low => extended_palette.background.strong
+                };
+
+                style.background(Background::Color(pair.color)).color(pair.text)
             }).padding(5))
+
+            // Estatus
+            .push(self.status_badge(labels))
         )
         // Descripción
-        .push(
-            container(
-                scrollable(
-                    text(&self.task.description)
-                    .width(Length::Fill)
-                ).height(Length::Shrink)
-            ).max_height(75)
-        )
+        .push({
+            let description = self.task.searchable_description();
+            let clamped = clamp_text(description, DESCRIPTION_LINES, DESCRIPTION_CHARS);
+
+            column![]
+            .push(container(match &clamped {
+                // Las tareas importadas pueden no tener descripción
+                _ if description.is_empty() => text("(sin descripción)").style(text::secondary).into(),
+                Some(clamped) if !self.expanded => highlighted(clamped, query),
+                _ => highlighted(description, query)
+            }).width(Length::Fill))
+            // Solo las descripciones largas tienen el botón
+            .push_maybe(clamped.map(|_| {
+                button(if self.expanded {"Mostrar menos"} else {"Mostrar más"})
+                .on_press(Message::ToggleExpanded)
+                .style(button::text)
+                .padding(0)
+            }))
+            .spacing(5)
+        })
+        // Pasos
+        .push_maybe((!self.task.subtasks.is_empty()).then(|| {
+            let (done, total) = self.task.subtask_progress();
+
+            column![]
+            .push(text!("{}/{} completadas", done, total).style(text::secondary).size(12))
+            .extend(self.task.subtasks.iter().enumerate().map(|(index, subtask)| {
+                checkbox(&subtask.title, subtask.done)
+                .on_toggle(move |_| Message::ToggleSubtask(index))
+                .into()
+            }))
+            .spacing(5)
+        }))
+        // Etiquetas
+        .push_maybe((!self.task.tags.is_empty()).then(|| {
+            Wrap::with_elements(self.task.tags.iter().map(|tag| {
+                button(text!("#{}", tag).size(12))
+                .on_press(Message::FilterTag(tag.clone()))
+                .style(button::secondary)
+                .padding([2, 6])
+                .into()
+            }).collect())
+            .spacing(5)
+            .line_spacing(5)
+        }))
         .push(
             {
                 let column = column![]
+                // Fecha límite
+                .push_maybe(self.task.due_date.map(|due_date| {
+                    text!("Vence: {}", format_date_time_as(due_date, date_format, locale()))
+                    .style(if self.task.is_overdue() {text::danger} else {text::secondary})
+                }))
                 // Creación
-                .push(text!("Creado: {}", format_date_time(self.task.created_at)).style(text::secondary));
+                .push(relative_time("Creado", self.task.created_at, date_format))
+                // Tiempo en progreso
+                .push_maybe({
+                    let time = self.task.time_in_progress(Utc::now().naive_utc());
+                    (!time.is_zero()).then(|| text!("Tiempo en progreso: {}", format_duration(time)).style(text::secondary))
+                })
+                // Estimado, junto a lo que falta según el tiempo en progreso
+                .push_maybe(self.task.estimate_minutes.map(|estimate| {
+                    let spent = (self.task.time_in_progress(Utc::now().naive_utc()).as_secs() / 60) as u32;
+                    match spent {
+                        0 => text!("Estimado: {}", format_minutes(estimate)).style(text::secondary),
+                        spent if spent <= estimate => {
+                            text!("Estimado: {} (faltan {})", format_minutes(estimate), format_minutes(estimate - spent)).style(text::secondary)
+                        }
+                        spent => text!("Estimado: {} (excedido por {})", format_minutes(estimate), format_minutes(spent - estimate)).style(text::danger)
+                    }
+                }))
+                // Completado
+                .push_maybe(self.task.completed_at.map(|completed_at| {
+                    text!("Completado: {}", format_timestamp_as(completed_at, date_format, locale())).style(text::secondary)
+                }));
                 
                 // Edición
-                if self.task.created_at != self.task.modified_at {
-                    column.push(
-                        text!("Última modificación: {}", format_date_time(self.task.modified_at)).style(text::secondary)
-                    )
+                if self.task.is_edited() {
+                    column.push(relative_time("Última modificación", self.task.modified_at, date_format))
                 }else {
                     column
                 }        
@@ -248,9 +1129,28 @@ impl TaskView {
         .push(row![]
             // Editar
             .push(button("Editar").on_press(Message::ToggleState))
+            // Archivar, las tareas solo se eliminan definitivamente desde el archivo
+            .push(
+                button(if self.task.archived {"Desarchivar"} else {"Archivar"})
+                .on_press(Message::ToggleArchive(self.task.id))
+            )
             // Eliminar
-            .push(button("Eliminar").on_press(Message::Delete(self.task.id)))
+            .push_maybe((self.task.archived || self.pending_delete).then(|| if self.pending_delete {
+                button("¿Confirmar?").on_press(Message::ConfirmDelete).style(button::danger)
+            }else {
+                button("Eliminar").on_press(Message::RequestDelete).style(button::danger)
+            }))
+            .push_maybe(self.pending_delete.then(|| {
+                button("Cancelar").on_press(Message::CancelDelete).style(button::secondary)
+            }))
+            // Duplicar
+            .push(button("Duplicar").on_press(Message::Duplicate(self.task.id)).style(button::secondary))
+            // Copiar como texto
+            .push(button("Copiar").on_press_with(|| Message::CopyToClipboard(task_to_clipboard_text(&self.task))).style(button::secondary))
             .push(horizontal_space())
+            // Orden manual
+            .push(button("↑").on_press(Message::MoveUp).style(button::secondary))
+            .push(button("↓").on_press(Message::MoveDown).style(button::secondary))
             .spacing(10)
         )
         .padding(10)
@@ -259,7 +1159,7 @@ impl TaskView {
     }
 
     // Vista de edición
-    fn edit_view(&self) -> Element<Message> {
+    fn edit_view(&self) -> Element<'_, Message> {
         column![]
         .push(row![]
             // Titulo
@@ -273,39 +1173,85 @@ impl TaskView {
             .push(combo_box(
                 &self.fields.combo_state, 
                 "Estado...", 
-                Some(&self.fields.status), 
-                Message::SetStatus
+                self.fields.status_options.iter().find(|option| option.status == self.fields.status), 
+                |option| Message::SetStatus(option.status)
             ).width(Length::Fixed(100.0)))
+            // Prioridad
+            .push(combo_box(
+                &self.fields.priority_combo_state,
+                "Prioridad...",
+                Some(&self.fields.priority),
+                Message::SetPriority
+            ).width(Length::Fixed(80.0)))
+            .spacing(5)
         )
+        .push_maybe(self.fields.transition_error.map(|error| text(error.to_string()).size(12).style(text::danger)))
         .push(
             // Descripción
-            container(
+            container(if self.fields.preview {
+                description_preview(&self.fields.text_editor_content.text())
+            } else {
                 text_editor(&self.fields.text_editor_content)
                 .placeholder("Descripción...")
                 .on_action(Message::SetDescription)
                 .height(Length::Fill)
-            ).height(75)
+                .into()
+            }).height(editor_height(&self.fields.text_editor_content))
+        )
+        .push(
+            row![]
+            .push(length_counter(&self.fields.text_editor_content.text(), DESCRIPTION_MAX_CHARS))
+            .push(horizontal_space())
+            .push(preview_toggle(self.fields.preview, Message::TogglePreview))
+            .align_y(Vertical::Center)
+        )
+        // Etiquetas
+        .push(
+            text_input("Etiquetas separadas por comas...", &self.fields.tags)
+            .on_input(Message::SetTags)
+        )
+        // Fecha límite
+        .push(self.due_date_input())
+        // Estimado
+        .push(
+            row![]
+            .push(text_input("Estimado en minutos...", &self.fields.estimate).on_input(Message::SetEstimate).width(Length::Fixed(200.0)))
+            .push_maybe(self.fields.estimate().map(|minutes| text(format_minutes(minutes)).style(text::secondary)))
+            .spacing(10)
+            .align_y(Vertical::Center)
+        )
+        // Color
+        .push(self.color_input())
+        // Dependencias
+        .push(self.dependencies_input())
+        // Pasos
+        .push(
+            column![]
+            .extend(self.fields.subtasks.iter().enumerate().map(|(index, subtask)| {
+                row![]
+                .push(text(&subtask.title).width(Length::Fill))
+                .push(button("Quitar").on_press(Message::RemoveSubtask(index)).style(button::text))
+                .align_y(Vertical::Center)
+                .into()
+            }))
+            .push(
+                row![]
+                .push(
+                    text_input("Nuevo paso...", &self.fields.new_subtask)
+                    .on_input(Message::SetNewSubtask)
+                    .on_submit(Message::AddSubtask)
+                )
+                .push(button("Agregar").on_press(Message::AddSubtask).style(button::secondary))
+                .spacing(5)
+            )
+            .spacing(5)
         )
         // Botones de acción
         .push(row![].push(
             // Aceptar edición
-            button("Aceptar").on_press_with(||{
-                let title = if !self.fields.title.is_empty() && self.fields.title != self.task.title {
-                    Some(self.fields.title.clone())
-                }else {None};
-
-                let description = if self.fields.text_editor_content.text().trim() != self.task.description {
-                    Some(self.fields.text_editor_content.text().trim().to_string())
-                }else {None};
-                
-                let status = if self.fields.status != self.task.status {
-                    Some(self.fields.status)
-                }else {None};
-
-                Message::Modify { title, description, status }
-            }))
+            button("Aceptar").on_press_with(|| self.accept_message()))
             // Cancelar edición
-            .push(button("Cancelar").on_press(Message::ToggleState))
+            .push(button("Cancelar").on_press(Message::CancelEdit))
             .push(horizontal_space())
             .spacing(10)
         )
@@ -313,19 +1259,389 @@ impl TaskView {
         .spacing(10)
         .into()
     }
+
+    /// Mensaje que envía "Aceptar": solo lleva los campos que cambiaron respecto a la tarea
+    pub fn accept_message(&self) -> Message {
+        // Un título o una descripción en blanco no se guardan, se conserva el valor anterior
+        let title = self.fields.title.trim();
+        let title = if !title.is_empty() && title != self.task.title {
+            Some(title.to_string())
+        }else {None};
+
+        let description = self.fields.description();
+        let description = if !description.is_empty() && description != self.task.description {
+            Some(description)
+        }else {None};
+        
+        let status = if self.fields.status != self.task.status {
+            Some(self.fields.status)
+        }else {None};
+
+        let due_date = if self.fields.due_date != self.task.due_date {
+            Some(self.fields.due_date)
+        }else {None};
+
+        let priority = if self.fields.priority != self.task.priority {
+            Some(self.fields.priority)
+        }else {None};
+
+        let tags = parse_tags(&self.fields.tags);
+        let tags = if tags != self.task.tags {
+            Some(tags)
+        }else {None};
+
+        let subtasks = if self.fields.subtasks != self.task.subtasks {
+            Some(self.fields.subtasks.clone())
+        }else {None};
+
+        let color = if self.fields.color != self.task.color {
+            Some(self.fields.color)
+        }else {None};
+
+        let depends_on = if self.fields.depends_on != self.task.depends_on {
+            Some(self.fields.depends_on.clone())
+        }else {None};
+
+        let estimate = self.fields.estimate();
+        let estimate_minutes = if estimate != self.task.estimate_minutes {
+            Some(estimate)
+        }else {None};
+
+        Message::Modify { title, description, status, due_date, priority, tags, subtasks, color, depends_on, estimate_minutes }
+    }
+
+    /// Indica si "Aceptar" guardaría algún cambio
+    pub fn has_changes(&self) -> bool {
+        !matches!(self.accept_message(), Message::Modify {
+            title: None, description: None, status: None, due_date: None, priority: None,
+            tags: None, subtasks: None, color: None, depends_on: None, estimate_minutes: None
+        })
+    }
+
+    /// Lista de dependencias elegidas y selector para agregar otra
+    fn dependencies_input(&self) -> Element<'_, Message> {
+        let title = |id: &Uuid| self.fields.dependency_options.iter()
+            .find(|option| option.id == *id)
+            .map_or("(tarea eliminada)".to_string(), |option| option.title.clone());
+
+        column![]
+        .extend(self.fields.depends_on.iter().enumerate().map(|(index, id)| {
+            row![]
+            .push(text!("Depende de: {}", title(id)).width(Length::Fill))
+            .push(button("Quitar").on_press(Message::RemoveDependency(index)).style(button::text))
+            .align_y(Vertical::Center)
+            .into()
+        }))
+        .push(combo_box(
+            &self.fields.dependency_combo_state,
+            "Agregar dependencia...",
+            None,
+            |option: DependencyOption| Message::AddDependency(option.id)
+        ))
+        .spacing(5)
+        .into()
+    }
+
+    /// Muestras de [COLORS] para elegir el color de acento, y un botón para quitarlo
+    fn color_input(&self) -> Element<'_, Message> {
+        row![]
+        .push(text("Color:"))
+        .extend(COLORS.iter().map(|&color| {
+            let selected = self.fields.color == Some(color);
+
+            button(horizontal_space().width(16))
+            .height(20)
+            .on_press(Message::SetColor(Some(color)))
+            .style(move |theme: &Theme, _status| button::Style {
+                background: Some(Background::Color(rgb(color))),
+                border: iced::Border {
+                    color: if selected {theme.palette().text} else {iced::Color::TRANSPARENT},
+                    width: 2.0,
+                    radius: 4.0.into()
+                },
+                ..button::Style::default()
+            })
+            .into()
+        }))
+        .push_maybe(self.fields.color.map(|_| button("Sin color").on_press(Message::SetColor(None)).style(button::text)))
+        .spacing(5)
+        .align_y(Vertical::Center)
+        .into()
+    }
+
+    /// Selectores de fecha y hora para la fecha límite
+    fn due_date_input(&self) -> Element<'_, Message> {
+        let due_date = self.fields.due_date;
+        let date = due_date.map(|due_date| due_date.date()).unwrap_or(Local::now().date_naive());
+        let time = due_date.map(|due_date| due_date.time()).unwrap_or_default();
+
+        row![]
+        .push(text("Vence:"))
+        .push(date_picker(
+            self.fields.picker == Some(Picker::Date),
+            date,
+            button(text(match due_date {
+                Some(due_date) => due_date.format("%d/%m/%Y").to_string(),
+                None => "Sin fecha".to_string()
+            })).on_press(Message::SetPicker(Some(Picker::Date))).style(button::secondary),
+            Message::SetPicker(None),
+            Message::SetDueDate
+        ))
+        .push(time_picker(
+            self.fields.picker == Some(Picker::Time),
+            time,
+            button(text(time.format("%H:%M").to_string()))
+            .on_press_maybe(due_date.map(|_| Message::SetPicker(Some(Picker::Time))))
+            .style(button::secondary),
+            Message::SetPicker(None),
+            Message::SetDueTime
+        ).use_24h())
+        .push_maybe(due_date.map(|_| button("Quitar").on_press(Message::ClearDueDate).style(button::text)))
+        .spacing(10)
+        .align_y(Vertical::Center)
+        .into()
+    }
+}
+
+/// Color de la estrella de las tareas favoritas
+const FAVORITE_COLOR: [u8; 3] = [241, 196, 15];
+
+/// Convierte un color RGB guardado en una [Task] al tipo de iced
+fn rgb([red, green, blue]: [u8; 3]) -> iced::Color {
+    iced::Color::from_rgb8(red, green, blue)
+}
+
+/// Texto con las coincidencias de `query` en negrita y subrayadas
+fn highlighted<'a>(content: &str, query: &str) -> Element<'a, Message> {
+    let parts = split_matches(content, query);
+
+    if parts.iter().all(|(_, matched)| !matched) {
+        return text(content.to_string()).into()
+    }
+
+    let bold = Font { weight: font::Weight::Bold, ..Font::DEFAULT };
+    let spans: Vec<Span<'a, Message>> = parts.into_iter()
+        .map(|(part, matched)| {
+            let part = span(part.to_string());
+            if matched {part.font(bold).underline(true)} else {part}
+        })
+        .collect();
+
+    rich_text(spans).into()
+}
+
+/// Fecha en formato relativo ("hace 2 horas"), con la fecha completa en un tooltip
+fn relative_time<'a, M: 'a>(label: &str, date_time: NaiveDateTime, format: &str) -> Element<'a, M> {
+    tooltip(
+        text!("{}: {}", label, format_relative(date_time, format, locale())).style(text::secondary),
+        text(format_timestamp_as(date_time, format, locale())).size(12),
+        tooltip::Position::Top
+    )
+    .style(container::rounded_box)
+    .padding(5)
+    .into()
+}
+
+/// Alto del editor según la cantidad de líneas, entre [EDITOR_MIN_HEIGHT] y [EDITOR_MAX_HEIGHT].
+/// La vista previa usa el mismo alto para que el formulario no salte al alternar.
+pub fn editor_height(content: &text_editor::Content) -> f32 {
+    (content.line_count() as f32 * EDITOR_LINE_HEIGHT + EDITOR_PADDING).clamp(EDITOR_MIN_HEIGHT, EDITOR_MAX_HEIGHT)
+}
+
+/// Vista previa de solo lectura de la descripción, con saltos de línea y énfasis simple, ver [parse_emphasis]
+pub fn description_preview<'a, M: Clone + 'static>(content: &str) -> Element<'a, M> {
+    let spans: Vec<Span<'a, M>> = parse_emphasis(content.trim_end()).into_iter()
+        .map(|(part, emphasis)| {
+            let font = Font {
+                weight: if emphasis.bold {font::Weight::Bold} else {font::Weight::Normal},
+                style: if emphasis.italic {font::Style::Italic} else {font::Style::Normal},
+                ..if emphasis.code {Font::MONOSPACE} else {Font::DEFAULT}
+            };
+            span(part).font(font)
+        })
+        .collect();
+
+    if spans.is_empty() {
+        return text("(sin descripción)").style(text::secondary).into()
+    }
+
+    container(scrollable(rich_text(spans)).height(Length::Fill).width(Length::Fill))
+    .style(container::bordered_box)
+    .padding(5)
+    .into()
+}
+
+/// Botón para alternar entre el editor de la descripción y su vista previa
+pub fn preview_toggle<'a, M: Clone + 'a>(preview: bool, on_press: M) -> Element<'a, M> {
+    button(text(if preview {"Editar"} else {"Vista previa"}).size(12))
+    .on_press(on_press)
+    .style(button::text)
+    .padding(2)
+    .into()
+}
+
+/// Contador informativo de palabras y caracteres para mostrar bajo un editor de texto
+pub fn length_counter<'a, M: 'a>(content: &str, max_chars: usize) -> Element<'a, M> {
+    let (words, chars) = text_stats(content);
+
+    text!("{} palabras · {} caracteres", words, chars)
+    .size(12)
+    .style(if chars > max_chars {text::danger} else {text::secondary})
+    .into()
+}
+
+impl Field {
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Texto del editor de descripción, sin el salto de línea final que añade el editor
+    pub fn description(&self) -> String {
+        self.text_editor_content.text().trim().to_string()
+    }
+
+    /// Línea y columna del cursor en el editor de descripción
+    pub fn cursor_position(&self) -> (usize, usize) {
+        self.text_editor_content.cursor_position()
+    }
+
+    /// Minutos estimados escritos en el formulario, [None] si el campo está vacío
+    pub fn estimate(&self) -> Option<u32> {
+        self.estimate.parse().ok()
+    }
+}
+
+/// Nombre de un estado en singular (etiqueta de la tarjeta) y en plural (filtros y secciones)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StatusLabel {
+    pub singular: String,
+    pub plural: String
+}
+
+impl StatusLabel {
+    fn new(singular: &str, plural: &str) -> Self {
+        StatusLabel { singular: singular.to_string(), plural: plural.to_string() }
+    }
+}
+
+/// Nombres y orden con que se muestran los estados, configurables en las preferencias.
+/// Solo afectan a la interfaz: los archivos siempre guardan los estados con su nombre en serde.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct StatusLabels {
+    /// Orden de los filtros, las secciones y el selector de estado
+    pub order: Vec<Status>,
+    pub to_do: StatusLabel,
+    pub in_progress: StatusLabel,
+    pub done: StatusLabel
+}
+
+impl Default for StatusLabels {
+    fn default() -> Self {
+        StatusLabels {
+            order: vec![Status::ToDo, Status::InProgress, Status::Done],
+            to_do: StatusLabel::new("Pendiente", "Pendientes"),
+            in_progress: StatusLabel::new("En progreso", "En progreso"),
+            done: StatusLabel::new("Terminada", "Terminadas")
+        }
+    }
+}
+
+impl StatusLabels {
+    pub fn get(&self, status: Status) -> &StatusLabel {
+        match status {
+            Status::ToDo => &self.to_do,
+            Status::InProgress => &self.in_progress,
+            Status::Done => &self.done
+        }
+    }
+
+    /// Estados en el orden configurado. Los repetidos se ignoran y los que falten se agregan al final.
+    pub fn order(&self) -> Vec<Status> {
+        let mut order: Vec<Status> = Vec::new();
+        for status in self.order.iter().chain([Status::ToDo, Status::InProgress, Status::Done].iter()) {
+            if !order.contains(status) {
+                order.push(*status);
+            }
+        }
+        order
+    }
+}
+
+/// Opción del selector de estado: un estado mostrado con su nombre configurado
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusOption {
+    pub status: Status,
+    pub label: String
+}
+
+impl Display for StatusOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+/// Opciones del selector de estado en el orden de `labels`
+fn status_options(labels: &StatusLabels) -> Vec<StatusOption> {
+    labels.order().into_iter()
+        .map(|status| StatusOption { status, label: labels.get(status).singular.clone() })
+        .collect()
+}
+
+/// Opción del selector de dependencias: una tarea mostrada por su título
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencyOption {
+    pub id: Uuid,
+    pub title: String,
+}
+
+impl Display for DependencyOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.title)
+    }
+}
+
+impl From<&Task> for Field {
+    fn from(task: &Task) -> Self {
+        Field { 
+            title: task.title.clone(), 
+            status: task.status, 
+            status_options: status_options(&StatusLabels::default()),
+            combo_state: combo_box::State::new(status_options(&StatusLabels::default())),
+            priority: task.priority,
+            priority_combo_state: combo_box::State::new(Priority::ALL.to_vec()),
+            tags: task.tags.join(", "),
+            subtasks: task.subtasks.clone(),
+            new_subtask: String::new(),
+            depends_on: task.depends_on.clone(),
+            dependency_options: Vec::new(),
+            dependency_combo_state: combo_box::State::new(Vec::new()),
+            text_editor_content: text_editor::Content::with_text(&(task.description.clone())),
+            due_date: task.due_date,
+            picker: None,
+            color: task.color,
+            estimate: task.estimate_minutes.map(|minutes| minutes.to_string()).unwrap_or_default(),
+            preview: false,
+            transition_error: None
+        }
+    }
 }
 
 impl From<&Task> for TaskView {
     fn from(task: &Task) -> Self {
         TaskView { 
             state: State::Static, 
-            fields: Field { 
-                title: task.title.clone(), 
-                status: task.status.clone(), 
-                combo_state: combo_box::State::new(Status::ALL.to_vec()), 
-                text_editor_content: text_editor::Content::with_text(&(task.description.clone()))
-            },
-            task: task.to_owned()
+            fields: Field::from(task),
+            task: task.to_owned(),
+            pending_delete: false,
+            expanded: false,
+            focused: false,
+            auto_in_progress: false,
+            strict_transitions: false
         }
     }
 }
@@ -1,82 +1,1266 @@
-use chrono::NaiveDateTime;
-use iced::widget::text_editor;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::ops::Range;
+use std::{fs, io};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc};
+use iced::widget::{scrollable, text_editor};
+use iced::{window, Point, Size, Theme};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::task::{self, Status, Task, TaskView};
-use crate::utils::{read_tasks, write_tasks};
+use crate::task::{self, DependencyOption, Priority, Status, Task, TaskView};
+use crate::history::{HistoryEntry, HistoryLog, Operation, HISTORY_FILE};
+use crate::settings::{load_settings, save_settings, settings_path, Preferences, Settings, ThemeMode, WindowState};
+use crate::utils::{default_tasks_path, utc_to_local, format_date_time_as, locale, merge_tasks, modified_at, parse_quick_add, parse_tasks, FileStamp, export_csv, export_jsonl, export_markdown, import_csv, import_external, import_jsonl, read_tasks, write_tasks, ReadError, CSV_FILE, JSONL_FILE, MARKDOWN_FILE};
 
 #[derive(Debug)]
 pub struct TaskTracker {
     pub tasks: Vec<TaskView>,
+
+    /// Ruta del archivo donde se guardan las tareas
+    pub path: PathBuf,
     
     pub title: String,
     pub description: text_editor::Content,
+    /// Se muestra la vista previa de la descripción en lugar del editor
+    pub description_preview: bool,
+    /// Campos obligatorios que faltaron al intentar crear una tarea
+    pub form_errors: FormErrors,
+    /// Tarea con título repetido que espera confirmación para crearse
+    pub pending_create: Option<(String, String)>,
+    /// Se pidió limpiar las tareas terminadas y falta confirmar
+    pub pending_clear: bool,
+    /// Se pidió eliminar las tareas filtradas y falta confirmar
+    pub pending_remove_filtered: bool,
+    /// Está abierto el diálogo para crear una tarea
+    pub creating: bool,
+    /// Ventana cuyo cierre espera confirmación porque hay texto sin guardar
+    pub pending_close: Option<window::Id>,
+
+    pub filter: Query,
+    pub sort: SortBy,
+    /// Secciones por estado que están abiertas cuando la lista se agrupa, ver [TaskTracker::list_rows]
+    pub open_sections: StatusFilter,
+    /// Está abierto el panel de "Filtros avanzados"
+    pub advanced_filters: bool,
+    /// Selector de fecha abierto en los filtros avanzados
+    pub range_picker: Option<RangeBound>,
+    /// Está abierto el panel de estadísticas
+    pub show_stats: bool,
+    /// Está abierto el panel de preferencias
+    pub show_preferences: bool,
+
+    /// Advertencia no fatal que se muestra en la interfaz (p. ej. archivo de tareas dañado)
+    pub warning: Option<String>,
+    /// Aviso informativo que se muestra en la interfaz (p. ej. exportación completada)
+    pub notice: Option<String>,
+
+    /// Última tarea eliminada junto a su posición, para poder deshacer la eliminación
+    pub last_deleted: Option<(usize, Task)>,
+    /// Última tarea editada tal como estaba antes de aceptar los cambios, y cuándo se aceptaron
+    pub last_edit: Option<(Task, Instant)>,
+
+    /// Último tamaño y posición conocidos de la ventana
+    pub window: Option<WindowState>,
+
+    /// Preferencias de la interfaz (tema)
+    pub preferences: Preferences,
+
+    /// Desplazamiento y alto visible de la lista, para construir solo las tarjetas cercanas
+    pub scroll_offset: f32,
+    pub viewport_height: f32,
+
+    /// Historial de operaciones, se escribe solo si [Preferences::history_log] está activado
+    pub history: HistoryLog,
+
+    /// Hay cambios en las tareas que aún no se escriben en disco
+    dirty: bool,
+    /// Hay cambios en las preferencias que se guardan con la siguiente escritura diferida
+    preferences_dirty: bool,
+    /// El archivo de tareas es de una versión más nueva, ver [ReadError::Version], y no se sobrescribe
+    read_only: bool,
+    /// Huella del archivo de tareas la última vez que se leyó o escribió
+    synced: Option<FileStamp>,
+    /// Tareas eliminadas desde la última sincronización, para que no reaparezcan al fusionar
+    removed: HashSet<Uuid>,
+    /// Tareas ya notificadas, junto a si el aviso fue por estar vencida.
+    /// Una tarea que vence hoy se vuelve a avisar una sola vez cuando pasa su hora.
+    notified: HashSet<(Uuid, bool)>
+}
+/// Tiempo durante el que se ofrece deshacer una edición aceptada
+pub const UNDO_EDIT_WINDOW: Duration = Duration::from_secs(10);
+
+/// Desde cuántas tareas visibles se construyen solo las tarjetas cercanas a la parte visible de la lista
+pub const VIRTUALIZE_FROM: usize = 100;
+/// Alto estimado de una tarjeta (con su separación) en cada modo, usado para ubicar la parte visible
+pub const CARD_HEIGHT: f32 = 185.0;
+pub const COMPACT_CARD_HEIGHT: f32 = 45.0;
+/// Tarjetas extra que se construyen antes y después de la parte visible
+const OVERSCAN: usize = 10;
+/// Alto que se asume para la lista hasta recibir el primer desplazamiento
+const DEFAULT_VIEWPORT_HEIGHT: f32 = 800.0;
+
+/// Rango de las `len` tarjetas que hay que construir para una lista desplazada `offset` píxeles
+/// con un alto visible de `height`, asumiendo que cada tarjeta mide `item_height`.
+pub fn visible_window(len: usize, offset: f32, height: f32, item_height: f32) -> Range<usize> {
+    let first = (offset.max(0.0) / item_height) as usize;
+    let count = (height.max(0.0) / item_height).ceil() as usize + 1;
+
+    let start = first.saturating_sub(OVERSCAN).min(len);
+    let end = (first + count + OVERSCAN).min(len);
+    start..end
+}
+
+/// Filtra y ordena las tareas según su puntaje para `query`. El orden es estable, por lo que
+/// los empates conservan el orden recibido. Si ninguna tarea contiene `query` textualmente,
+/// se usan las coincidencias aproximadas.
+fn rank<'a>(tasks: Vec<&'a TaskView>, query: &str) -> Vec<&'a TaskView> {
+    if query.trim().is_empty() {
+        return tasks;
+    }
+
+    let exact: Vec<&TaskView> = tasks.iter().copied().filter(|tv| tv.get_task().matches(query)).collect();
+    let mut scored: Vec<(u32, &TaskView)> = if exact.is_empty() {tasks} else {exact}
+        .into_iter()
+        .filter_map(|tv| tv.get_task().score(query).map(|score| (score, tv)))
+        .collect();
+
+    scored.sort_by_key(|(score, _)| Reverse(*score));
+    scored.into_iter().map(|(_, tv)| tv).collect()
+}
+
+/// Cantidad de tareas que conservaría [rank], sin calcular su orden
+fn rank_count<'a>(tasks: impl Iterator<Item = &'a TaskView> + Clone, query: &str) -> usize {
+    if query.trim().is_empty() {
+        return tasks.count();
+    }
+
+    let any_exact = tasks.clone().any(|tv| tv.get_task().matches(query));
+    tasks
+        .filter(|tv| !any_exact || tv.get_task().matches(query))
+        .filter(|tv| tv.get_task().score(query).is_some())
+        .count()
+}
+
+/// Reasigna posiciones consecutivas conservando el orden actual.
+/// Los archivos antiguos tienen todas las posiciones en 0 y se ordenan por su lugar en el archivo.
+fn normalize_order(tasks: &mut [Task]) {
+    let mut indices: Vec<usize> = (0..tasks.len()).collect();
+    indices.sort_by_key(|&index| tasks[index].order);
 
-    pub filter: Query
+    for (order, index) in indices.into_iter().enumerate() {
+        tasks[index].order = order as u32;
+    }
+}
+
+/// Asigna un nuevo id a las tareas cuyo id ya apareció antes en la lista, p. ej. tras una mezcla
+/// mal resuelta o una edición manual. La primera aparición conserva su id. Retorna cuántas se cambiaron.
+fn dedupe_ids(tasks: &mut [Task]) -> usize {
+    let mut seen = HashSet::new();
+    let mut count = 0;
+
+    for task in tasks.iter_mut() {
+        while !seen.insert(task.id()) {
+            task.regenerate_id();
+            count += 1;
+        }
+    }
+
+    count
 }
+
+/// Advertencia que se muestra cuando [dedupe_ids] tuvo que cambiar algún id
+fn duplicate_ids_warning(count: usize) -> String {
+    format!("Se encontraron {} tareas con un id repetido; se les asignó un id nuevo", count)
+}
+
 impl Default for TaskTracker {
     fn default() -> Self {
-        TaskTracker {
-            tasks: read_tasks().iter().map(|task| TaskView::from(task)).collect(),
-            title: String::new(),
-            description: text_editor::Content::new(),
-            
-            filter: Query { text: String::new(), status: None }
-        }
+        TaskTracker::with_path(default_tasks_path())
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Query {
     pub text: String,
-    pub status: Option<Status>
+    pub status: StatusFilter,
+    pub tag: Option<String>,
+    /// Mostrar solo las tareas archivadas en lugar de las activas
+    pub archived: bool,
+    /// Mostrar solo las tareas creadas o que vencen este día (hora local), ver [Task::falls_on]
+    pub date: Option<NaiveDate>,
+    /// Primer y último día de creación (hora local, inclusive); [None] no limita ese lado
+    pub created_from: Option<NaiveDate>,
+    pub created_to: Option<NaiveDate>
+}
+
+impl Query {
+    /// Límites de [Query::created_from] y [Query::created_to] como instantes, para [Task::created_between]
+    pub fn created_range(&self) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
+        (
+            self.created_from.map(|day| day.and_time(NaiveTime::MIN)),
+            self.created_to.and_then(|day| day.and_hms_nano_opt(23, 59, 59, 999_999_999))
+        )
+    }
+
+    /// Si hay algún límite de fecha de creación
+    pub fn has_created_range(&self) -> bool {
+        self.created_from.is_some() || self.created_to.is_some()
+    }
+
+    /// Si el filtro o la búsqueda dejan fuera alguna tarea activa
+    pub fn is_filtering(&self) -> bool {
+        !self.text.trim().is_empty()
+            || !self.status.is_all()
+            || self.tag.is_some()
+            || self.archived
+            || self.date.is_some()
+            || self.has_created_range()
+    }
+}
+
+/// Resumen de las tareas activas (no archivadas) para el panel de estadísticas, ver [TaskTracker::stats]
+/// * _`created_this_week:`_ Creadas desde el lunes de la semana actual, en hora local
+/// * _`average_open_age:`_ Tiempo promedio desde que se crearon las tareas sin terminar; [None] si no hay
+/// * _`completion_rate:`_ Igual que [TaskTracker::completion_ratio]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Stats {
+    pub to_do: usize,
+    pub in_progress: usize,
+    pub done: usize,
+    pub created_this_week: usize,
+    pub average_open_age: Option<TimeDelta>,
+    pub completion_rate: f32
+}
+
+/// Extremo del rango de fechas de creación cuyo selector está abierto
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RangeBound {
+    From,
+    To
+}
+
+/// Fila de la lista de tareas: el encabezado de una sección o una tarjeta
+pub enum ListRow<'a> {
+    /// Encabezado de la sección de un estado, con la cantidad de tareas que contiene
+    Section(Status, usize),
+    Task(&'a TaskView)
+}
+
+/// Estados que se muestran con el filtro, cada uno se puede activar por separado.
+/// Por defecto se muestran todos
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct StatusFilter {
+    pub to_do: bool,
+    pub in_progress: bool,
+    pub done: bool
+}
+
+impl Default for StatusFilter {
+    fn default() -> Self {
+        StatusFilter { to_do: true, in_progress: true, done: true }
+    }
+}
+
+impl StatusFilter {
+    /// Filtro que solo muestra `status`
+    pub fn only(status: Status) -> Self {
+        let mut filter = StatusFilter { to_do: false, in_progress: false, done: false };
+        filter.toggle(status);
+        filter
+    }
+
+    fn flag(&mut self, status: Status) -> &mut bool {
+        match status {
+            Status::ToDo => &mut self.to_do,
+            Status::InProgress => &mut self.in_progress,
+            Status::Done => &mut self.done
+        }
+    }
+
+    pub fn contains(&self, status: Status) -> bool {
+        match status {
+            Status::ToDo => self.to_do,
+            Status::InProgress => self.in_progress,
+            Status::Done => self.done
+        }
+    }
+
+    /// Activa o desactiva `status` sin tocar los demás
+    pub fn toggle(&mut self, status: Status) {
+        let flag = self.flag(status);
+        *flag = !*flag;
+    }
+
+    /// Si están activos todos los estados ("Todas")
+    pub fn is_all(&self) -> bool {
+        *self == StatusFilter::default()
+    }
+
+    /// Si no hay ningún estado activo
+    pub fn is_empty(&self) -> bool {
+        !(self.to_do || self.in_progress || self.done)
+    }
+
+    /// El mismo filtro, o todos los estados si no queda ninguno activo; una lista siempre vacía no sirve como filtro
+    pub fn or_all(self) -> Self {
+        if self.is_empty() {StatusFilter::default()} else {self}
+    }
+}
+
+/// Indica qué campos del formulario de creación están vacíos
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FormErrors {
+    pub title: bool,
+    pub description: bool
+}
+
+impl FormErrors {
+    /// Valida el titulo y la descripción de una nueva tarea
+    pub fn validate(title: &str, description: &str) -> Self {
+        FormErrors {
+            title: title.trim().is_empty(),
+            description: description.trim().is_empty()
+        }
+    }
+
+    pub fn any(&self) -> bool {
+        self.title || self.description
+    }
+}
+
+/// Criterio de ordenamiento de la lista de tareas
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortBy {
+    /// Orden elegido por el usuario, ver [Task::order]
+    #[default]
+    Manual,
+    CreatedAsc,
+    CreatedDesc,
+    ModifiedDesc,
+    /// Las tareas abiertas más recientemente primero, ver [Task::last_viewed_at]
+    ViewedDesc,
+    TitleAsc,
+    /// Por estado en el orden del flujo de trabajo, ver [Status::rank]
+    StatusAsc
+}
+
+impl SortBy {
+    pub const ALL: &'static [Self] = &[
+        SortBy::Manual, SortBy::CreatedAsc, SortBy::CreatedDesc, SortBy::ModifiedDesc,
+        SortBy::ViewedDesc, SortBy::TitleAsc, SortBy::StatusAsc
+    ];
+}
+
+impl Display for SortBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            SortBy::Manual => "Manual",
+            SortBy::CreatedAsc => "Más antiguas",
+            SortBy::CreatedDesc => "Más recientes",
+            SortBy::ModifiedDesc => "Modificadas",
+            SortBy::ViewedDesc => "Vistas",
+            SortBy::TitleAsc => "Título",
+            SortBy::StatusAsc => "Estado"
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Delete(Uuid),
+    Duplicate(Uuid),
+    ToggleArchive(Uuid),
     SetTitle(String),
     SetDescription(text_editor::Action),
 
     SetQueryText(String),
-    SetQueryStatus(Option<Status>),
+    ToggleQueryStatus(Status),
+    ShowAllStatuses,
+    SetQueryTag(Option<String>),
+    ShowArchived,
+    SetSort(SortBy),
+    BulkSetStatus(Status),
 
     Create(String, String),
+    /// Crea una tarea con el contenido actual del formulario (Ctrl/Cmd + Enter).
+    /// Se ignora si la creación es en un diálogo y este está cerrado.
+    CreateFromForm,
+    ConfirmCreate,
+    CancelCreate,
+    SetWarnDuplicates(bool),
+    SetAutoInProgress(bool),
+    SetEditNewTasks(bool),
+    SetHistoryLog(bool),
+    SetDateTimeFormat(String),
+    SetStaleAfterDays(String),
+    SetStrictTransitions(bool),
+    SetViewMode(task::ViewMode),
+    OpenCreate,
+    /// Cierra el diálogo de creación descartando el borrador
+    CloseCreate,
+    SetCreateInModal(bool),
+    /// Alterna entre el editor de la descripción nueva y su vista previa
+    ToggleDescriptionPreview,
+    /// Abre o cierra la sección de un estado en la lista agrupada
+    ToggleSection(Status),
+    /// Activa o quita el filtro de las tareas de hoy
+    ToggleToday,
+    /// Abre o cierra el panel de "Filtros avanzados"
+    ToggleAdvancedFilters,
+    /// Abre o cierra el panel de estadísticas
+    ToggleStats,
+    /// Abre o cierra el panel de preferencias
+    TogglePreferences,
+    SetRangePicker(Option<RangeBound>),
+    /// Cambia un extremo del rango de fechas de creación; [None] lo quita
+    SetCreatedRange(RangeBound, Option<NaiveDate>),
+    /// Deshace la última edición aceptada de la tarea
+    UndoEdit(Uuid),
+    ListScrolled(scrollable::Viewport),
 
     TaskMessage(Uuid, task::Message),
 
+    DismissWarning,
+    DismissNotice,
+    ExportMarkdown,
+    ExportCsv,
+    ImportCsv,
+    ExportJsonl,
+    ImportJsonl,
+    /// Abre el selector de archivos para importar la exportación de otra aplicación
+    ImportExternal,
+    /// Archivo elegido en el selector, [None] si se canceló
+    ExternalFilePicked(Option<PathBuf>),
+    /// Abre el directorio del archivo de tareas en el explorador de archivos
+    OpenStorageFolder,
+    UndoDelete,
+    SetTheme(ThemeMode),
+    /// Se presionó Escape: cancela las acciones pendientes
+    Escape,
+    /// Click en un espacio vacío de la lista: solo cancela las eliminaciones pendientes
+    ClickAway,
+    /// Campo que tenía el foco al presionar Escape, si lo había
+    EscapeFocused(Option<iced::advanced::widget::Id>),
+    /// Ctrl+F: lleva el foco al campo de búsqueda
+    FocusSearch,
+    ToggleFocusedEdit,
+    SelectNext,
+    SelectPrev,
+    /// Flecha abajo (`true`) o arriba: selecciona la tarea siguiente o anterior si ningún campo tiene el foco
+    ArrowSelect(bool),
+    /// Resultado de revisar el foco para [Message::ArrowSelect]: la dirección y si algún campo tenía el foco
+    ArrowSelectFocused(bool, bool),
+    RequestDeleteFocused,
+    SetAllExpanded(bool),
+    RequestClearCompleted,
+    ClearCompleted,
+    CancelClearCompleted,
+    RequestRemoveFiltered,
+    RemoveFiltered,
+    CancelRemoveFiltered,
+
     FocusNext,
-    FocusPrev
+    FocusPrev,
+
+    // Eventos de la ventana
+    WindowResized(Size),
+    WindowMoved(Point),
+    CloseRequested(window::Id),
+    ConfirmClose,
+    CancelClose,
+
+    /// Escribe en disco los cambios pendientes
+    Flush,
+    /// Revisa las fechas límite y muestra las notificaciones nuevas
+    Notify,
+    SetNotifications(bool),
+    /// Revisa si el archivo de tareas cambió fuera de la aplicación
+    CheckFile
+}
+
+/// Error al cargar las tareas de un [TaskTracker] desde disco.
+/// Que el archivo no exista no es un error: se crea vacío.
+#[derive(Debug)]
+pub struct LoadError {
+    pub path: PathBuf,
+    pub error: ReadError
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.error, self.path.display())
+    }
 }
 
 impl TaskTracker {
-    pub fn add_task(&mut self, title: String, description: String) {
-        self.tasks.push(TaskView::from(Task::new(title, description)));
+    /// Carga las tareas desde la ruta por defecto, ver [default_tasks_path].
+    pub fn load() -> Result<Self, LoadError> {
+        TaskTracker::load_from(default_tasks_path())
+    }
+
+    /// Carga las tareas desde `path`. Si el archivo no existe se obtiene un [TaskTracker] vacío,
+    /// pero si existe y no se puede leer o está dañado se retorna un error.
+    pub fn load_from(path: PathBuf) -> Result<Self, LoadError> {
+        match read_tasks(&path) {
+            Ok(tasks) => {
+                let mut tracker = TaskTracker::new(path, tasks);
+                tracker.mark_synced();
+                Ok(tracker)
+            },
+            Err(error) => Err(LoadError { path, error })
+        }
+    }
+
+    /// Crea un [TaskTracker] vacío que muestra el error de carga como advertencia.
+    /// Si el archivo es de una versión más nueva, los cambios no se guardan en él.
+    pub fn from_load_error(error: LoadError) -> Self {
+        let warning = error.to_string();
+        let mut tracker = TaskTracker::new(error.path, Vec::new());
+        tracker.read_only = matches!(error.error, ReadError::Version(_));
+        tracker.warning = Some(warning);
+        tracker
+    }
+
+    /// Crea un [TaskTracker] que lee y guarda sus tareas en `path`.
+    /// Si las tareas no se pueden cargar, inicia vacío mostrando una advertencia.
+    pub fn with_path(path: PathBuf) -> Self {
+        TaskTracker::load_from(path).unwrap_or_else(TaskTracker::from_load_error)
+    }
+
+    fn new(path: PathBuf, mut tasks: Vec<Task>) -> Self {
+        normalize_order(&mut tasks);
+        let duplicates = dedupe_ids(&mut tasks);
+        let Settings { preferences, window } = load_settings(&settings_path(&path));
+
+        TaskTracker {
+            tasks: tasks.iter().map(TaskView::from).collect(),
+            title: String::new(),
+            description: text_editor::Content::new(),
+            description_preview: false,
+            form_errors: FormErrors::default(),
+            pending_create: None,
+            pending_clear: false,
+            pending_remove_filtered: false,
+            creating: false,
+            pending_close: None,
+            
+            filter: Query { text: preferences.filter_text.clone(), status: preferences.filter_status, tag: None, archived: false, date: None, created_from: None, created_to: None },
+            sort: preferences.sort,
+            open_sections: StatusFilter::default(),
+            advanced_filters: false,
+            range_picker: None,
+            show_stats: false,
+            show_preferences: false,
+
+            warning: (duplicates > 0).then(|| duplicate_ids_warning(duplicates)),
+            notice: None,
+            last_deleted: None,
+            last_edit: None,
+            window,
+            preferences,
+            scroll_offset: 0.0,
+            viewport_height: DEFAULT_VIEWPORT_HEIGHT,
+            history: HistoryLog::File(path.with_file_name(HISTORY_FILE)),
+            path,
+            // Los ids corregidos se escriben en el siguiente guardado
+            dirty: duplicates > 0,
+            preferences_dirty: false,
+            read_only: false,
+            synced: None,
+            removed: HashSet::new(),
+            notified: HashSet::new()
+        }
+    }
+
+    /// Crea una tarea al final de la lista y retorna su id
+    pub fn add_task(&mut self, title: String, description: String) -> Uuid {
+        self.push_task(Task::new(title, description))
+    }
+
+    /// Crea una tarea a partir de un título con atributos en línea, ver [parse_quick_add]
+    pub fn add_quick_task(&mut self, input: &str, description: String) -> Uuid {
+        let parsed = parse_quick_add(input);
+        let mut task = Task::new(parsed.title, description);
+        if let Some(priority) = parsed.priority {
+            task.set_priority(priority);
+        }
+        task.set_tags(parsed.tags);
+        task.set_due_date(parsed.due_date);
+        self.push_task(task)
+    }
+
+    fn push_task(&mut self, mut task: Task) -> Uuid {
+        task.order = self.next_order();
+        let id = task.id();
+        self.log_history(HistoryEntry::new(Operation::Create, &task));
+
+        self.tasks.push(TaskView::from(task));
+        self.last_deleted = None;
         
-        write_tasks(self.get_tasks());
+        self.save();
+        id
     }
 
-    pub fn remove_task(&mut self, id: Uuid) {
-        self.tasks.retain(|tv| tv.get_task().id() != id);
-        write_tasks(self.get_tasks());
+    /// Descarta el borrador del formulario de creación y cierra su diálogo
+    pub fn discard_draft(&mut self) {
+        self.creating = false;
+        self.title.clear();
+        self.description = text_editor::Content::new();
+        self.description_preview = false;
+        self.form_errors = FormErrors::default();
+        self.pending_create = None;
     }
 
-    pub fn update_task(&mut self, id: Uuid, title: Option<String>, description: Option<String>, status: Option<Status>) {
-        let task = self.get_task_mut(id);
+    /// Indica si hay texto que se perdería al cerrar: el formulario de creación o alguna tarea en edición
+    pub fn has_unsaved_input(&self) -> bool {
+        !self.title.trim().is_empty()
+            || !self.description.text().trim().is_empty()
+            || self.tasks.iter().any(|tv| tv.is_editing())
+    }
 
-        if let Some(task) = task {
-            if let Some(title) = title.clone() {
-                task.set_title(title);
+    /// Indica si alguna dependencia de la tarea `id` aún no está terminada.
+    /// Las dependencias que apuntan a tareas eliminadas se ignoran.
+    pub fn is_blocked(&self, id: Uuid) -> bool {
+        self.get_task(id).is_some_and(|task| {
+            task.depends_on.iter().any(|dependency| self.get_task(*dependency).is_some_and(|dependency| dependency.status != Status::Done))
+        })
+    }
+
+    /// Indica si `from` depende de `target`, directa o indirectamente
+    fn depends_on(&self, from: Uuid, target: Uuid) -> bool {
+        let mut pending = vec![from];
+        let mut visited = HashSet::new();
+
+        while let Some(id) = pending.pop() {
+            if id == target {
+                return true;
             }
-            if let Some(description) = description.clone() {
-                task.set_description(description);
+            if visited.insert(id) {
+                pending.extend(self.get_task(id).iter().flat_map(|task| task.depends_on.iter().copied()));
             }
-            if let Some(status) = status.clone() {
-                task.set_status(status);
+        }
+
+        false
+    }
+
+    /// Agrega `dependency` como dependencia de la tarea `id` y guarda.
+    /// Retorna `false` si alguna de las tareas no existe o si se formaría un ciclo.
+    pub fn add_dependency(&mut self, id: Uuid, dependency: Uuid) -> bool {
+        if self.get_task(dependency).is_none() || self.depends_on(dependency, id) {
+            return false;
+        }
+
+        let Some(task) = self.get_task_mut(id) else {
+            return false;
+        };
+        if !task.depends_on.contains(&dependency) {
+            task.depends_on.push(dependency);
+            task.modified();
+            self.save();
+        }
+        true
+    }
+
+    /// Quita las dependencias de la tarea `id` que forman un ciclo. Retorna si se quitó alguna.
+    pub fn remove_cyclic_dependencies(&mut self, id: Uuid) -> bool {
+        let Some(task) = self.get_task(id) else {
+            return false;
+        };
+        let cyclic: Vec<Uuid> = task.depends_on.iter().copied().filter(|&dependency| self.depends_on(dependency, id)).collect();
+        if cyclic.is_empty() {
+            return false;
+        }
+
+        if let Some(task) = self.get_task_mut(id) {
+            task.depends_on.retain(|dependency| !cyclic.contains(dependency));
+        }
+        true
+    }
+
+    /// Tareas que se pueden elegir como dependencia de `id`: todas menos ella misma y las que dependen de ella
+    pub fn dependency_options(&self, id: Uuid) -> Vec<DependencyOption> {
+        self.get_tasks_iter()
+            .filter(|task| !self.depends_on(task.id(), id))
+            .map(|task| DependencyOption { id: task.id(), title: task.title.clone() })
+            .collect()
+    }
+
+    /// Actualiza las opciones de dependencias de la tarea `id`, p. ej. al entrar a la vista de edición
+    pub fn refresh_dependency_options(&mut self, id: Uuid) {
+        let options = self.dependency_options(id);
+        if let Some(task_view) = self.tasks.iter_mut().find(|tv| tv.get_task().id() == id) {
+            task_view.set_dependency_options(options);
+        }
+    }
+
+    /// Indica si ya existe una tarea con el mismo título, sin importar mayúsculas ni espacios al inicio o final
+    pub fn has_duplicate_title(&self, title: &str) -> bool {
+        let title = title.trim().to_lowercase();
+        self.get_tasks_iter().any(|task| task.title.trim().to_lowercase() == title)
+    }
+
+    pub fn remove_task(&mut self, id: Uuid) {
+        self.remove_where(|task| task.id() == id);
+    }
+
+    /// Elimina todas las tareas que cumplen con `predicate`, guardando una sola vez, y retorna cuántas
+    /// se eliminaron. Si no hay ninguna no se escribe el archivo. Solo se puede deshacer la eliminación
+    /// de una única tarea, ver [TaskTracker::undo_delete].
+    pub fn remove_where<F: Fn(&Task) -> bool>(&mut self, predicate: F) -> usize {
+        let mut deleted: Vec<(usize, Task)> = Vec::new();
+        let mut index = 0;
+        self.tasks.retain(|task_view| {
+            let remove = predicate(task_view.get_task());
+            if remove {
+                deleted.push((index, Task::from(task_view)));
             }
+            index += 1;
+            !remove
+        });
+
+        if deleted.is_empty() {
+            return 0;
+        }
+
+        for (_, task) in &deleted {
+            self.log_history(HistoryEntry::new(Operation::Delete, task));
+            self.removed.insert(task.id());
+        }
+
+        let count = deleted.len();
+        self.last_deleted = if count == 1 {deleted.pop()} else {None};
+        self.save();
+        count
+    }
+
+    /// Elimina las tareas visibles con el filtro y la búsqueda actuales, ver [TaskTracker::remove_where]
+    pub fn remove_filtered(&mut self) -> usize {
+        let ids: HashSet<Uuid> = self.visible_tasks().iter().map(|tv| tv.get_task().id()).collect();
+        self.remove_where(|task| ids.contains(&task.id()))
+    }
+
+    /// Crea una copia de la tarea con un nuevo id y fechas actuales, agregando "(copia)" al titulo.
+    /// Retorna el id de la copia.
+    pub fn duplicate_task(&mut self, id: Uuid) -> Option<Uuid> {
+        let original = self.get_task(id)?;
+
+        let mut copy = Task::new(format!("{} (copia)", original.title), original.description.clone());
+        copy.set_status(original.status);
+        copy.set_priority(original.priority);
+        copy.set_tags(original.tags.clone());
+        copy.set_color(original.color);
+        copy.subtasks = original.subtasks.clone();
+
+        Some(self.push_task(copy))
+    }
+
+    /// Archiva todas las tareas terminadas que siguen en la lista normal, guardando una sola vez.
+    /// Retorna cuántas se archivaron.
+    pub fn clear_completed(&mut self) -> usize {
+        let mut count = 0;
+        for task in self.get_tasks_iter_mut().filter(|task| !task.archived && task.status == Status::Done) {
+            task.archived = true;
+            task.modified();
+            count += 1;
+        }
+
+        if count > 0 {
+            self.save();
+        }
+        count
+    }
+
+    /// Archiva la tarea si está activa, o la vuelve a la lista normal si está archivada
+    pub fn toggle_archive(&mut self, id: Uuid) {
+        if let Some(task) = self.get_task_mut(id) {
+            task.archived = !task.archived;
+            task.modified();
+            self.save();
+        }
+    }
+
+    /// Marca la tarea como favorita, o le quita la marca si ya la tenía
+    pub fn toggle_favorite(&mut self, id: Uuid) {
+        if let Some(task) = self.get_task_mut(id) {
+            task.favorite = !task.favorite;
+            task.modified();
+            self.save();
+        }
+    }
+
+    /// Restaura la última tarea eliminada en su posición original
+    pub fn undo_delete(&mut self) {
+        if let Some((index, task)) = self.last_deleted.take() {
+            self.removed.remove(&task.id());
+            self.tasks.insert(index.min(self.tasks.len()), TaskView::from(task));
+            self.save();
+        }
+    }
+
+    /// Agrega la entrada al historial si está activado. Un error al escribir se ignora para no interrumpir la operación.
+    pub fn log_history(&mut self, entry: HistoryEntry) {
+        if self.preferences.history_log {
+            let _ = self.history.append(entry);
+        }
+    }
+
+    /// Registra en el historial la edición de la tarea, si cambió respecto a `before`
+    pub fn log_edit(&mut self, before: &Task) {
+        let entry = self.get_task(before.id())
+            .filter(|task| task.differs_from(before))
+            .map(|task| HistoryEntry::new(Operation::Edit, task));
+
+        if let Some(entry) = entry {
+            self.log_history(entry);
+        }
+    }
+
+    /// Guarda cómo estaba la tarea antes de aceptar una edición, para poder deshacerla
+    pub fn record_edit(&mut self, before: Task) {
+        self.last_edit = Some((before, Instant::now()));
+    }
+
+    /// Indica si todavía se muestra la opción de deshacer la última edición, ver [UNDO_EDIT_WINDOW]
+    pub fn can_undo_edit(&self) -> bool {
+        self.last_edit.as_ref().is_some_and(|(_, at)| at.elapsed() < UNDO_EDIT_WINDOW)
+    }
+
+    /// Devuelve la tarea `id` a como estaba antes de su última edición aceptada
+    pub fn undo_edit(&mut self, id: Uuid) {
+        let Some((before, _)) = self.last_edit.take_if(|(task, _)| task.id() == id) else {return};
 
+        if let Some(task_view) = self.tasks.iter_mut().find(|tv| tv.get_task().id() == id) {
+            *task_view.get_task_mut() = before;
+            task_view.reset_fields();
+            self.save();
+        }
+    }
+
+    /// Siguiente posición libre al final del orden manual
+    fn next_order(&self) -> u32 {
+        self.get_tasks_iter().map(|task| task.order + 1).max().unwrap_or(0)
+    }
+
+    /// Intercambia la posición de la tarea con su vecina visible, arriba o abajo.
+    /// Cambia la lista al orden manual para que el movimiento sea visible. La vecina se busca por
+    /// [Task::order] entre las tareas filtradas sin el orden por relevancia, y solo dentro del mismo
+    /// grupo (favoritas o no), ya que las favoritas siempre se muestran primero.
+    pub fn move_task(&mut self, id: Uuid, up: bool) {
+        self.sort = SortBy::Manual;
+
+        let Some(favorite) = self.get_task(id).map(|task| task.favorite) else {return};
+        let mut tasks: Vec<&Task> = self.visible_tasks().into_iter()
+            .map(TaskView::get_task)
+            .filter(|task| task.favorite == favorite)
+            .collect();
+        tasks.sort_by_key(|task| task.order);
+        let ids: Vec<Uuid> = tasks.into_iter().map(Task::id).collect();
+        let Some(index) = ids.iter().position(|task_id| *task_id == id) else {
+            return;
+        };
+        let neighbor = match up {
+            true if index > 0 => ids[index - 1],
+            false if index + 1 < ids.len() => ids[index + 1],
+            _ => return
+        };
+
+        let order = self.get_task(id).map(|task| task.order).unwrap_or_default();
+        let neighbor_order = self.get_task(neighbor).map(|task| task.order).unwrap_or_default();
+        if let Some(task) = self.get_task_mut(id) {
+            task.order = neighbor_order;
             task.modified();
-            write_tasks(self.get_tasks());
+        }
+        if let Some(task) = self.get_task_mut(neighbor) {
+            task.order = order;
+            task.modified();
+        }
+
+        self.save();
+    }
+
+    /// Cancela la eliminación pendiente de todas las tareas
+    pub fn cancel_pending_deletes(&mut self) {
+        for task_view in self.tasks.iter_mut().filter(|tv| tv.is_pending_delete()) {
+            let _ = task_view.update(task::Message::CancelDelete);
+        }
+    }
+
+    /// Expande o recorta la descripción de todas las tareas. Es estado de la interfaz, no se guarda.
+    pub fn set_all_expanded(&mut self, expanded: bool) {
+        for task_view in self.tasks.iter_mut() {
+            task_view.set_expanded(expanded);
+        }
+    }
+
+    /// Sale de la vista de edición de la tarea seleccionada, igual que pulsar "Cancelar" en ella.
+    /// Las demás tareas en edición no se tocan.
+    pub fn cancel_focused_edit(&mut self) {
+        if let Some(task_view) = self.tasks.iter_mut().find(|tv| tv.is_focused() && tv.is_editing()) {
+            let _ = task_view.update(task::Message::CancelEdit);
+        }
+    }
+
+    /// Selecciona la tarea `id` para los atajos de teclado, deseleccionando las demás
+    pub fn focus_task(&mut self, id: Uuid) {
+        for task_view in self.tasks.iter_mut() {
+            task_view.set_focused(false);
+        }
+
+        if let Some(task_view) = self.tasks.iter_mut().find(|tv| tv.get_task().id() == id) {
+            let _ = task_view.update(task::Message::Focus);
+        }
+    }
+
+    /// Mueve la selección a la tarea siguiente (o anterior) en el orden en que se muestran.
+    /// Si no hay ninguna seleccionada se elige la primera (o la última).
+    pub fn select_adjacent(&mut self, forward: bool) {
+        let visible: Vec<Uuid> = self.visible_tasks().iter().map(|tv| tv.get_task().id()).collect();
+        let Some(&last) = visible.last() else {
+            return;
+        };
+
+        let current = visible.iter().position(|&id| self.tasks.iter().any(|tv| tv.is_focused() && tv.get_task().id() == id));
+        let id = match (current, forward) {
+            (Some(index), true) => visible[(index + 1).min(visible.len() - 1)],
+            (Some(index), false) => visible[index.saturating_sub(1)],
+            (None, true) => visible[0],
+            (None, false) => last
+        };
+
+        self.focus_task(id);
+    }
+
+    /// Pide confirmación para eliminar la tarea seleccionada
+    pub fn request_delete_focused(&mut self) {
+        if let Some(task_view) = self.tasks.iter_mut().find(|tv| tv.is_focused() && !tv.is_editing()) {
+            let _ = task_view.update(task::Message::RequestDelete);
+        }
+    }
+
+    /// Entra o sale de la vista de edición de la tarea seleccionada
+    pub fn toggle_focused_edit(&mut self) {
+        if let Some(task_view) = self.tasks.iter_mut().find(|tv| tv.is_focused()) {
+            let message = if task_view.is_editing() {task::Message::CancelEdit} else {task::Message::ToggleState};
+            let _ = task_view.update(message);
+
+            let id = task_view.get_task().id();
+            self.refresh_dependency_options(id);
+        }
+    }
+
+    /// Guarda el tamaño y la posición de la ventana junto al archivo de tareas
+    pub fn save_window_state(&self) {
+        if self.window.is_some() {
+            let _ = self.write_settings();
+        }
+    }
+
+    /// Exporta todas las tareas a "tasks.md", junto al archivo de tareas
+    pub fn export_markdown(&mut self) {
+        let path = self.path.with_file_name(MARKDOWN_FILE);
+
+        match export_markdown(&self.get_tasks(), &path) {
+            Ok(()) => self.notice = Some(format!("Tareas exportadas a {}", path.display())),
+            Err(error) => self.warning = Some(format!("No se pudo exportar a Markdown: {}", error))
+        }
+    }
+
+    /// Exporta todas las tareas a "tasks.csv", junto al archivo de tareas
+    pub fn export_csv(&mut self) {
+        let path = self.path.with_file_name(CSV_FILE);
+
+        match export_csv(&self.get_tasks(), &path) {
+            Ok(()) => self.notice = Some(format!("Tareas exportadas a {}", path.display())),
+            Err(error) => self.warning = Some(format!("No se pudo exportar a CSV: {}", error))
+        }
+    }
+
+    /// Importa las tareas de "tasks.csv" y las agrega a la lista actual.
+    /// Las tareas cuyo id ya existe se omiten.
+    pub fn import_csv(&mut self) {
+        let path = self.path.with_file_name(CSV_FILE);
+
+        match import_csv(&path) {
+            Ok(tasks) => {
+                let count = self.merge_tasks(tasks);
+                self.notice = Some(format!("Se importaron {} tareas desde {}", count, path.display()));
+            },
+            Err(error) => self.warning = Some(format!("No se pudo importar el CSV: {}", error))
+        }
+    }
+
+    /// Directorio donde está el archivo de tareas, el mismo que usan [read_tasks] y [write_tasks]
+    pub fn storage_dir(&self) -> PathBuf {
+        match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            // Una ruta relativa sin directorio, p. ej. "tasks.json"
+            _ => PathBuf::from(".")
+        }
+    }
+
+    /// Abre el directorio del archivo de tareas en el explorador de archivos del sistema.
+    /// Si aún no se guardó ninguna tarea el directorio se crea.
+    pub fn open_storage_folder(&mut self) {
+        let dir = self.storage_dir();
+
+        if let Err(error) = fs::create_dir_all(&dir).and_then(|_| open::that_detached(&dir)) {
+            self.warning = Some(format!("No se pudo abrir {}: {}", dir.display(), error));
+        }
+    }
+
+    /// Exporta todas las tareas a "tasks.jsonl", junto al archivo de tareas
+    pub fn export_jsonl(&mut self) {
+        let path = self.path.with_file_name(JSONL_FILE);
+
+        match export_jsonl(&self.get_tasks(), &path) {
+            Ok(()) => self.notice = Some(format!("Tareas exportadas a {}", path.display())),
+            Err(error) => self.warning = Some(format!("No se pudo exportar a JSON Lines: {}", error))
+        }
+    }
+
+    /// Importa las tareas de "tasks.jsonl" y las agrega a la lista actual, omitiendo las que ya existen.
+    /// Las líneas inválidas se informan en la advertencia sin detener la importación.
+    pub fn import_jsonl(&mut self) {
+        let path = self.path.with_file_name(JSONL_FILE);
+
+        match import_jsonl(&path) {
+            Ok((tasks, errors)) => {
+                let count = self.merge_tasks(tasks);
+                self.notice = Some(format!("Se importaron {} tareas desde {}", count, path.display()));
+                if !errors.is_empty() {
+                    let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                    self.warning = Some(format!("Se omitieron {} líneas: {}", errors.len(), errors.join("; ")));
+                }
+            },
+            Err(error) => self.warning = Some(format!("No se pudo importar {}: {}", path.display(), error))
+        }
+    }
+
+    /// Importa las tareas exportadas por otra aplicación (ver [import_external]) y las agrega a la lista actual
+    pub fn import_external_file(&mut self, path: &Path) {
+        let tasks = fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|json| import_external(&json).map_err(|error| error.to_string()));
+
+        match tasks {
+            Ok(tasks) => {
+                let count = self.merge_tasks(tasks);
+                self.notice = Some(format!("Se importaron {} tareas desde {}", count, path.display()));
+            },
+            Err(error) => self.warning = Some(format!("No se pudo importar {}: {}", path.display(), error))
+        }
+    }
+
+    /// Cambia a `status` todas las tareas visibles con el filtro y la búsqueda actuales,
+    /// guardando una sola vez. En el modo estricto se omiten las que no pueden pasar a `status`.
+    /// Retorna cuántas tareas cambiaron.
+    pub fn bulk_set_status(&mut self, status: Status) -> usize {
+        let strict = self.preferences.strict_transitions;
+        let ids: Vec<Uuid> = self.visible_tasks().iter()
+            .map(|tv| tv.get_task())
+            .filter(|task| task.status != status && task.status.check_transition(status, strict).is_ok())
+            .map(|task| task.id())
+            .collect();
+
+        if ids.is_empty() {
+            return 0
+        }
+
+        for task_view in self.tasks.iter_mut().filter(|tv| ids.contains(&tv.get_task().id())) {
+            let task = task_view.get_task_mut();
+            task.set_status(status);
+            task.modified();
+
+            if !task_view.is_editing() {
+                task_view.reset_fields();
+            }
+        }
+
+        self.save();
+        ids.len()
+    }
+
+    /// Agrega al final las tareas cuyo id no existe en la lista, registrándolas en el historial como creadas.
+    /// Retorna cuántas se agregaron.
+    pub fn merge_tasks(&mut self, tasks: Vec<Task>) -> usize {
+        let mut count = 0;
+        for task in tasks {
+            if self.get_task(task.id()).is_none() {
+                self.push_task(task);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Tema actual, si no se eligió uno se usa el del sistema
+    pub fn theme(&self) -> Theme {
+        match self.preferences.theme {
+            Some(ThemeMode::Light) => Theme::Light,
+            Some(ThemeMode::Dark) => Theme::Dark,
+            None => Theme::default()
+        }
+    }
+
+    /// Guarda la búsqueda, el filtro por estado y el orden actuales para restaurarlos al iniciar
+    pub fn save_view_state(&mut self) {
+        self.preferences.filter_text = self.filter.text.clone();
+        self.preferences.filter_status = self.filter.status;
+        self.preferences.sort = self.sort;
+        self.save_preferences();
+    }
+
+    /// Marca las preferencias para guardarlas con la siguiente escritura diferida, ver [TaskTracker::flush].
+    /// Para los campos de texto, que cambian con cada tecla.
+    pub fn save_preferences_later(&mut self) {
+        self.preferences_dirty = true;
+    }
+
+    /// Guarda las preferencias de la interfaz junto al archivo de tareas
+    pub fn save_preferences(&mut self) {
+        self.preferences_dirty = false;
+        if let Err(error) = self.write_settings() {
+            self.warning = Some(format!("No se pudieron guardar las preferencias: {}", error));
+        }
+    }
+
+    /// Escribe las preferencias y el estado de la ventana en "settings.json"
+    fn write_settings(&self) -> io::Result<()> {
+        let settings = Settings { preferences: self.preferences.clone(), window: self.window };
+        save_settings(&settings_path(&self.path), &settings)
+    }
+
+    /// Marca las tareas como pendientes de guardar. Se escriben en disco en el siguiente [TaskTracker::flush],
+    /// así varios cambios seguidos producen una sola escritura.
+    pub fn save(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Indica si hay cambios, en las tareas o en las preferencias, que aún no se escriben en disco
+    pub fn is_dirty(&self) -> bool {
+        self.dirty || self.preferences_dirty
+    }
+
+    /// Escribe las tareas en disco si hay cambios pendientes, junto con las preferencias de [TaskTracker::save_preferences_later].
+    /// Si el archivo también cambió fuera de la aplicación, primero se fusionan ambas versiones con [merge_tasks].
+    /// Si falla, se muestra una advertencia en lugar de cerrar la aplicación.
+    /// Un archivo de una versión más nueva del formato nunca se sobrescribe.
+    pub fn flush(&mut self) {
+        if self.preferences_dirty {
+            self.save_preferences();
+        }
+        if !self.dirty {
+            return;
+        }
+        if self.read_only {
+            self.dirty = false;
+            return;
+        }
+
+        self.merge_external_changes();
+        self.dirty = false;
+        match write_tasks(&self.path, self.get_tasks()) {
+            Ok(()) => self.mark_synced(),
+            Err(error) => self.warning = Some(format!("No se pudieron guardar las tareas: {}", error))
+        }
+    }
+
+    /// Registra la huella actual del archivo de tareas, para no confundir las escrituras propias con cambios externos
+    fn mark_synced(&mut self) {
+        self.synced = FileStamp::read(&self.path).ok().map(|(stamp, _)| stamp);
+        self.removed.clear();
+    }
+
+    /// Si el archivo cambió desde la última sincronización, incorpora sus tareas a las locales
+    /// en lugar de sobreescribirlas. Las tareas eliminadas aquí desde entonces no se recuperan.
+    fn merge_external_changes(&mut self) {
+        let Some(synced) = self.synced else {
+            return;
+        };
+        let Ok((stamp, contents)) = FileStamp::read(&self.path) else {
+            return;
+        };
+        if stamp.hash == synced.hash {
+            return;
+        }
+        let Ok(incoming) = parse_tasks(&contents) else {
+            return;
+        };
+
+        let incoming = incoming.into_iter().filter(|task| !self.removed.contains(&task.id())).collect();
+        let local = self.get_tasks().into_iter().cloned().collect();
+        self.reload(merge_tasks(local, incoming));
+    }
+
+    /// Revisa si el archivo de tareas cambió fuera de la aplicación y, de ser así, recarga las tareas.
+    /// Si hay cambios propios pendientes de guardar se espera al siguiente [TaskTracker::flush], que fusiona ambos.
+    pub fn check_for_changes(&mut self) {
+        if self.dirty || modified_at(&self.path) == self.synced.and_then(|stamp| stamp.modified) {
+            return;
+        }
+
+        let Ok((stamp, contents)) = FileStamp::read(&self.path) else {
+            return;
+        };
+        if self.synced.is_some_and(|synced| synced.hash == stamp.hash) {
+            self.synced = Some(stamp);
+            return;
+        }
+
+        // Si el archivo quedó a medio editar se espera al siguiente cambio
+        if let Ok(tasks) = parse_tasks(&contents) {
+            self.synced = Some(stamp);
+            self.reload(tasks);
+        }
+    }
+
+    /// Reemplaza las tareas por `tasks`, conservando el contenido de las tareas que se están editando.
+    /// Ver [TaskTracker::sync_from_tasks].
+    pub fn reload(&mut self, mut tasks: Vec<Task>) {
+        normalize_order(&mut tasks);
+        let duplicates = dedupe_ids(&mut tasks);
+        if duplicates > 0 {
+            self.warning = Some(duplicate_ids_warning(duplicates));
+            self.save();
+        }
+
+        self.sync_from_tasks(tasks);
+    }
+
+    /// Reconcilia las vistas con `tasks` por id, en el orden recibido: las tareas sin cambios conservan
+    /// su [TaskView] tal cual, las modificadas actualizan su tarea (y sus campos si no se están editando),
+    /// las nuevas se agregan y las que ya no están se quitan.
+    pub fn sync_from_tasks(&mut self, tasks: Vec<Task>) {
+        let mut views: HashMap<Uuid, TaskView> = self.tasks.drain(..).map(|tv| (tv.get_task().id(), tv)).collect();
+
+        self.tasks = tasks.into_iter().map(|task| match views.remove(&task.id()) {
+            Some(task_view) if *task_view.get_task() == task => task_view,
+            Some(mut task_view) => {
+                *task_view.get_task_mut() = task;
+                if !task_view.is_editing() {
+                    task_view.reset_fields();
+                }
+                task_view
+            }
+            None => TaskView::from(task)
+        }).collect();
+    }
+
+    pub fn update_task(&mut self, id: Uuid, title: Option<String>, description: Option<String>, status: Option<Status>) {
+        let strict = self.preferences.strict_transitions;
+        let task = self.get_task_mut(id);
+
+        if let Some(task) = task {
+            match task.try_modify(title, description, status, strict) {
+                Ok(()) => self.save(),
+                Err(error) => self.warning = Some(error.to_string())
+            }
         }
     }
 
@@ -104,16 +1288,224 @@ impl TaskTracker {
         self.tasks.iter().filter(move |task_view| task_view.get_task().status == status)
     }
 
+    pub fn by_priority(&self, priority: Priority) -> impl Iterator<Item = &TaskView> {
+        self.tasks.iter().filter(move |task_view| task_view.get_task().priority == priority)
+    }
+
+    /// Obtiene las tareas que cumplen con el filtro actual, ordenadas según [SortBy].
+    /// Las favoritas van primero. El ordenamiento es estable, por lo que los empates conservan el orden de inserción.
+    pub fn visible_tasks(&self) -> Vec<&TaskView> {
+        let mut tasks: Vec<&TaskView> = self.tasks.iter()
+            .filter(|tv| self.matches_filter(tv.get_task()))
+            .collect();
+
+        match self.sort {
+            SortBy::Manual => tasks.sort_by_key(|tv| tv.get_task().order),
+            SortBy::CreatedAsc => tasks.sort_by_key(|tv| tv.get_task().created_at()),
+            SortBy::CreatedDesc => tasks.sort_by_key(|tv| Reverse(tv.get_task().created_at())),
+            SortBy::ModifiedDesc => tasks.sort_by_key(|tv| Reverse(tv.get_task().modified_at())),
+            SortBy::ViewedDesc => tasks.sort_by_key(|tv| Reverse(tv.get_task().last_viewed_at)),
+            SortBy::TitleAsc => tasks.sort_by_cached_key(|tv| tv.get_task().title.to_lowercase()),
+            SortBy::StatusAsc => tasks.sort_by_key(|tv| tv.get_task().status)
+        }
+
+        // Las favoritas van primero sin alterar el orden dentro de cada grupo
+        let mut tasks = rank(tasks, &self.filter.text);
+        tasks.sort_by_key(|tv| !tv.get_task().favorite);
+        tasks
+    }
+
+    /// Obtiene hasta `limit` tareas visibles a partir de `offset`, con el filtro y el orden de [TaskTracker::visible_tasks].
+    /// Si `offset` pasa del final retorna una lista vacía.
+    pub fn page(&self, offset: usize, limit: usize) -> Vec<&Task> {
+        self.visible_tasks().into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(TaskView::get_task)
+            .collect()
+    }
+
+    /// Filas de la lista. Sin filtro por estado las tareas visibles se agrupan en secciones, en el
+    /// orden de [crate::task::StatusLabels]; de las secciones cerradas solo queda el encabezado.
+    /// Con un filtro por estado la lista es plana.
+    pub fn list_rows(&self) -> Vec<ListRow<'_>> {
+        let tasks = self.visible_tasks();
+        if !self.filter.status.is_all() {
+            return tasks.into_iter().map(ListRow::Task).collect()
+        }
+
+        let mut rows = Vec::new();
+        for status in self.preferences.status_labels.order() {
+            let section: Vec<&TaskView> = tasks.iter().copied().filter(|tv| tv.get_task().status == status).collect();
+            if section.is_empty() {
+                continue;
+            }
+
+            rows.push(ListRow::Section(status, section.len()));
+            if self.open_sections.contains(status) {
+                rows.extend(section.into_iter().map(ListRow::Task));
+            }
+        }
+        rows
+    }
+
+    /// Cantidad de tareas que cumplen con el filtro y la búsqueda actuales.
+    /// Las cuenta sin ordenarlas ni reunirlas, ya que la vista lo consulta en cada pintado.
+    pub fn visible_count(&self) -> usize {
+        rank_count(self.tasks.iter().filter(|tv| self.matches_filter(tv.get_task())), &self.filter.text)
+    }
+
+    /// Cantidad total de tareas, incluidas las archivadas
+    pub fn total_count(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Indica si `task` cumple con el filtro por archivo, estado y etiqueta (sin la búsqueda de texto)
+    fn matches_filter(&self, task: &Task) -> bool {
+        let (start, end) = self.filter.created_range();
+
+        task.archived == self.filter.archived
+            && self.filter.status.contains(task.status)
+            && self.filter.tag.as_ref().is_none_or(|tag| task.has_tag(tag))
+            && self.filter.date.is_none_or(|day| task.falls_on(day))
+            && task.created_between(start, end)
+    }
+
+    /// Busca tareas por titulo, descripción y etiquetas, ordenadas por puntaje de coincidencia.
+    /// Si ninguna coincide textualmente, se usa la búsqueda aproximada.
+    pub fn search(&self, query: &str) -> Vec<&Task> {
+        rank(self.tasks.iter().collect(), query).into_iter().map(|tv| tv.get_task()).collect()
+    }
+
+    /// Tareas que no están archivadas
+    pub fn active_tasks(&self) -> impl Iterator<Item = &TaskView> {
+        self.tasks.iter().filter(|task_view| !task_view.get_task().archived)
+    }
+
+    pub fn archived_tasks(&self) -> impl Iterator<Item = &TaskView> {
+        self.tasks.iter().filter(|task_view| task_view.get_task().archived)
+    }
+
+    /// Proporción de tareas activas terminadas, entre 0.0 y 1.0. Si no hay tareas es 0.0.
+    pub fn completion_ratio(&self) -> f32 {
+        let (to_do, in_progress, done) = self.status_counts();
+        let total = to_do + in_progress + done;
+
+        if total == 0 {0.0} else {done as f32 / total as f32}
+    }
+
+    /// Tareas activas sin terminar que no se modifican desde hace más de `older_than`
+    pub fn stale_tasks(&self, older_than: Duration) -> Vec<&Task> {
+        self.stale_tasks_at(Utc::now().naive_utc(), older_than)
+    }
+
+    /// Igual que [TaskTracker::stale_tasks], tomando `now` (en UTC) como el momento actual
+    pub fn stale_tasks_at(&self, now: NaiveDateTime, older_than: Duration) -> Vec<&Task> {
+        self.active_tasks().map(TaskView::get_task).filter(|task| task.is_stale_at(now, older_than)).collect()
+    }
+
+    /// Tiempo sin cambios tras el que una tarea se marca "sin actividad"; [None] si está desactivado
+    pub fn stale_threshold(&self) -> Option<Duration> {
+        let days = self.preferences.stale_after_days;
+        (days > 0).then(|| Duration::from_secs(u64::from(days) * 24 * 60 * 60))
+    }
+
+    /// Si `task` se marca "sin actividad" con el umbral de las preferencias
+    pub fn is_stale(&self, task: &Task) -> bool {
+        self.stale_threshold().is_some_and(|threshold| task.is_stale(threshold))
+    }
+
+    /// Estadísticas de las tareas activas en este momento
+    pub fn stats(&self) -> Stats {
+        self.stats_at(Utc::now().naive_utc())
+    }
+
+    /// Estadísticas de las tareas activas tomando `now` (en UTC) como el momento actual
+    pub fn stats_at(&self, now: NaiveDateTime) -> Stats {
+        let active = |status| self.by_status(status).filter(|tv| !tv.get_task().archived).map(TaskView::get_task);
+        let (to_do, in_progress, done) = (active(Status::ToDo).count(), active(Status::InProgress).count(), active(Status::Done).count());
+
+        let today = utc_to_local(now).date();
+        let monday = today - TimeDelta::days(today.weekday().num_days_from_monday() as i64);
+        let created_this_week = self.active_tasks()
+            .filter(|tv| (monday..=today).contains(&utc_to_local(tv.get_task().created_at()).date()))
+            .count();
+
+        let open_ages: Vec<TimeDelta> = active(Status::ToDo).chain(active(Status::InProgress))
+            .map(|task| now - task.created_at())
+            .collect();
+        let average_open_age = (!open_ages.is_empty())
+            .then(|| open_ages.iter().sum::<TimeDelta>() / open_ages.len() as i32);
+
+        Stats { to_do, in_progress, done, created_this_week, average_open_age, completion_rate: self.completion_ratio() }
+    }
+
+    /// Cantidad de tareas activas por estado: (pendientes, en progreso, terminadas)
+    pub fn status_counts(&self) -> (usize, usize, usize) {
+        let count = |status| self.active_tasks().filter(|tv| tv.get_task().status == status).count();
+
+        (count(Status::ToDo), count(Status::InProgress), count(Status::Done))
+    }
+
+    pub fn by_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a TaskView> {
+        self.tasks.iter().filter(move |task_view| task_view.get_task().has_tag(tag))
+    }
+
+    /// Tareas creadas el mismo día que `date` (hora local), sin importar la hora
     pub fn get_tasks_by_date(&self, date: NaiveDateTime) -> Vec<&Task> {
-        self.get_tasks_iter().filter(|task| task.created_at() == date).collect()
+        self.filter_tasks(|task| utc_to_local(task.created_at()).date() == date.date()).collect()
+    }
+
+    /// Tareas que cumplen `predicate`, en el orden en que están guardadas.
+    /// Es la base de las demás consultas y se puede combinar con cualquier condición.
+    pub fn filter_tasks<F: Fn(&Task) -> bool>(&self, predicate: F) -> impl Iterator<Item = &Task> {
+        self.get_tasks_iter().filter(move |task| predicate(task))
     }
 
     pub fn by_title_or_description(&self, query: &str) -> Vec<&Task> {
-        self.get_tasks_iter().filter(|task| task.title.contains(query) || task.description.contains(query)).collect()
+        self.filter_tasks(|task| task.matches(query)).collect()
     }
 
+    /// Tareas creadas entre `start` y `end` inclusive, en hora local
     pub fn get_tasks_by_date_range(&self, start: NaiveDateTime, end: NaiveDateTime) -> Vec<&Task> {
-        self.get_tasks_iter().filter(|task| task.created_at() >= start && task.created_at() <= end).collect()
+        self.filter_tasks(|task| task.created_between(Some(start), Some(end))).collect()
+    }
+
+    /// Tareas sin terminar cuya fecha límite cae en el día actual (hora local)
+    pub fn due_today(&self) -> Vec<&Task> {
+        let today = Local::now().date_naive();
+        self.filter_tasks(|task| task.status != Status::Done && task.due_date.is_some_and(|due_date| due_date.date() == today))
+        .collect()
+    }
+
+    /// Tareas sin terminar cuya fecha límite ya pasó
+    pub fn overdue(&self) -> Vec<&Task> {
+        self.filter_tasks(Task::is_overdue).collect()
+    }
+
+    /// Textos de las notificaciones para las tareas vencidas o que vencen hoy que aún no se
+    /// avisaron. Cada tarea se avisa una vez por vencer hoy y otra al quedar vencida.
+    pub fn take_notifications(&mut self) -> Vec<String> {
+        let overdue = self.overdue().into_iter().map(|task| (task, true));
+        let due_today = self.due_today().into_iter().filter(|task| !task.is_overdue()).map(|task| (task, false));
+
+        let pending: Vec<((Uuid, bool), String)> = overdue.chain(due_today)
+            .filter(|(task, overdue)| !self.notified.contains(&(task.id(), *overdue)))
+            .filter_map(|(task, overdue)| {
+                let due_date = task.due_date?;
+                let text = if overdue {
+                    format!("Vencida: {} ({})", task.title, format_date_time_as(due_date, &self.preferences.date_time_format, locale()))
+                }else {
+                    format!("Vence hoy a las {}: {}", due_date.format("%H:%M"), task.title)
+                };
+                Some(((task.id(), overdue), text))
+            })
+            .collect();
+
+        pending.into_iter().map(|(key, text)| {
+            self.notified.insert(key);
+            text
+        }).collect()
     }
 }
 
@@ -1,4 +1,77 @@
-use crate::task::Task;
+use std::fs;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{Local, Locale, NaiveDate, TimeDelta, TimeZone};
+use iced::widget::text_editor;
+
+use crate::api::{DtoError, TaskDto};
+use crate::cli;
+use crate::history::{HistoryEntry, HistoryLog, Operation};
+use crate::task::{self, Priority, Status, StatusLabel, StatusLabels, Task, TaskView, TransitionError, ViewMode};
+use crate::task_tracker::{visible_window, FormErrors, ListRow, Message, RangeBound, SortBy, Stats, StatusFilter, TaskTracker, VIRTUALIZE_FROM};
+use crate::utils::{clamp_text, export_csv, export_markdown, format_age, format_date_time, format_date_time_as, format_duration, try_format_localized, is_valid_date_time_format, format_minutes, format_relative_to, format_timestamp, export_jsonl, import_csv, import_external, import_jsonl, local_to_utc, merge_tasks, migrate, parse_emphasis, parse_tasks, parse_quick_add_at, parse_tags, read_tasks, split_matches, task_to_clipboard_text, text_stats, truncate_chars, utc_to_local, utc_timestamp, write_atomic, write_tasks, Emphasis, ParsedTask, DEFAULT_DATE_TIME_FORMAT, DEFAULT_LOCALE, FALLBACK_DATE_TIME_FORMAT};
+use crate::settings::{load_settings, save_settings, Preferences, Settings, ThemeMode, WindowState};
+
+/// Directorio temporal que se borra, con todo su contenido, al salir de la prueba
+struct TempDir(PathBuf);
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Crea un directorio temporal único para las pruebas que escriben en disco.
+fn temp_dir() -> TempDir {
+    let dir = std::env::temp_dir().join(format!("task_tracker_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    TempDir(dir)
+}
+
+/// [TaskTracker] con su archivo de tareas en un [TempDir] propio
+struct TempTracker {
+    tracker: TaskTracker,
+    dir: TempDir
+}
+
+impl Deref for TempTracker {
+    type Target = TaskTracker;
+
+    fn deref(&self) -> &TaskTracker {
+        &self.tracker
+    }
+}
+
+impl DerefMut for TempTracker {
+    fn deref_mut(&mut self) -> &mut TaskTracker {
+        &mut self.tracker
+    }
+}
+
+fn temp_tracker() -> TempTracker {
+    temp_tracker_in(temp_dir())
+}
+
+/// Como [temp_tracker], para las pruebas que preparan archivos en `dir` antes de cargar
+fn temp_tracker_in(dir: TempDir) -> TempTracker {
+    TempTracker { tracker: TaskTracker::with_path(dir.join("tasks.json")), dir }
+}
 
 #[test]
 fn serialize_deserialize_task() {
@@ -11,4 +84,2536 @@ fn serialize_deserialize_task() {
     let des = serde_json::from_str(ser.as_str()).unwrap_or(Task::new("Error".to_string(), "".to_string()));
 
     assert_eq!(task, des)
-}
\ No newline at end of file
+}
+
+#[test]
+fn write_atomic_leaves_no_temp_file() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+
+    let task = Task::new("Test", "Test Write");
+    write_atomic(&path, &serde_json::to_string(&vec![&task]).unwrap()).unwrap();
+
+    // El archivo final existe y el temporal no
+    assert!(path.exists());
+    assert!(!dir.join("tasks.json.tmp").exists());
+
+    let des: Vec<Task> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(des, vec![task]);
+}
+
+#[test]
+fn read_write_tasks_with_path() {
+    let dir = temp_dir();
+    let path = dir.join("nested").join("tasks.json");
+
+    // Si no existe, se crea vacío
+    assert!(read_tasks(&path).unwrap().is_empty());
+    assert!(path.exists());
+
+    let task = Task::new("Test", "Test Path");
+    write_tasks(&path, vec![&task]).unwrap();
+    assert_eq!(read_tasks(&path).unwrap(), vec![task]);
+
+    // Se guarda indentado, una propiedad por línea
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.lines().count() > 1);
+    assert!(contents.contains("\n      \"title\": \"Test\""));
+}
+
+#[test]
+fn tracker_with_path() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+
+    let mut tracker = TaskTracker::with_path(path.clone());
+    tracker.add_task("Test".to_string(), "Test Tracker".to_string());
+    tracker.flush();
+
+    let reloaded = TaskTracker::with_path(path);
+    assert_eq!(reloaded.get_tasks(), tracker.get_tasks());
+}
+
+#[test]
+fn add_task_returns_new_id() {
+    let mut tracker = temp_tracker();
+
+    let first = tracker.add_task("Primera".to_string(), "Uno".to_string());
+    let second = tracker.add_task("Segunda".to_string(), "Dos".to_string());
+    assert_ne!(first, second);
+
+    assert_eq!(tracker.get_task(first).unwrap().title, "Primera");
+    assert_eq!(tracker.get_task(second).unwrap().description, "Dos");
+}
+
+#[test]
+fn import_external_maps_fields() {
+    let json = r#"[
+        {"id": "2995104339", "content": "Comprar leche", "description": "Deslactosada", "checked": false, "priority": 4, "labels": ["casa"]},
+        {"id": "2995104340", "content": "  Pagar la luz ", "description": null, "checked": true, "due": {"date": "2024-05-20"}},
+        {"content": "Viejo formato", "checked": 1},
+        {"content": "   ", "checked": 0}
+    ]"#;
+
+    let tasks = import_external(json).unwrap();
+    assert_eq!(tasks.len(), 3);
+
+    assert_eq!(tasks[0].title, "Comprar leche");
+    assert_eq!(tasks[0].description, "Deslactosada");
+    assert_eq!(tasks[0].status, Status::ToDo);
+    assert!(tasks[0].tags.is_empty());
+
+    assert_eq!(tasks[1].title, "Pagar la luz");
+    assert_eq!(tasks[1].description, "");
+    assert_eq!(tasks[1].status, Status::Done);
+    assert!(tasks[1].completed_at().is_some());
+
+    assert_eq!(tasks[2].status, Status::Done);
+    assert_ne!(tasks[0].id(), tasks[1].id());
+
+    // Un archivo que no es un arreglo es un error
+    assert!(import_external(r#"{"content": "Suelta"}"#).is_err());
+    assert!(import_external(r#"[{"title": "Sin content"}]"#).is_err());
+}
+
+#[test]
+fn import_external_file_merges_into_tracker() {
+    let dir = temp_dir();
+    let path = dir.join("export.json");
+    fs::write(&path, r#"[{"content": "Importada", "description": "De otra app", "checked": false}]"#).unwrap();
+
+    let mut tracker = temp_tracker_in(dir);
+    tracker.add_task("Existente".to_string(), "Ya estaba".to_string());
+    let _ = tracker.update(Message::ExternalFilePicked(Some(path)));
+    assert_eq!(tracker.get_tasks().len(), 2);
+    assert_eq!(tracker.get_tasks()[1].title, "Importada");
+    assert!(tracker.notice.is_some());
+
+    // Un archivo inexistente muestra una advertencia sin cambiar la lista
+    let missing = tracker.dir.join("no-existe.json");
+    let _ = tracker.update(Message::ExternalFilePicked(Some(missing)));
+    assert_eq!(tracker.get_tasks().len(), 2);
+    assert!(tracker.warning.is_some());
+}
+
+#[test]
+fn storage_dir_is_next_to_tasks_file() {
+    let mut tracker = temp_tracker();
+    assert_eq!(tracker.storage_dir(), tracker.dir.to_path_buf());
+
+    // Una ruta sin directorio se resuelve al directorio de trabajo
+    tracker.path = PathBuf::from("tasks.json");
+    assert_eq!(tracker.storage_dir(), PathBuf::from("."));
+}
+
+#[test]
+fn deserialize_task_without_due_date() {
+    let json = r#"{
+        "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+        "title": "Test",
+        "description": "Sin fecha límite",
+        "status": "to-do",
+        "created_at": "2025-01-20T10:00:00",
+        "modified_at": "2025-01-20T10:00:00"
+    }"#;
+
+    let task: Task = serde_json::from_str(json).unwrap();
+
+    assert_eq!(task.title, "Test");
+    assert_eq!(task.due_date, None);
+    assert!(!task.is_overdue());
+    assert_eq!(task.priority, Priority::Medium);
+    assert!(task.tags.is_empty());
+    assert_eq!(task.completed_at(), None);
+}
+
+#[test]
+fn tracker_by_priority() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Normal".to_string(), "Media".to_string());
+    tracker.add_task("Urgente".to_string(), "Alta".to_string());
+
+    let id = tracker.get_tasks()[1].id();
+    tracker.get_task_mut(id).unwrap().set_priority(Priority::High);
+
+    let high: Vec<_> = tracker.by_priority(Priority::High).map(|tv| tv.get_task().title.clone()).collect();
+    assert_eq!(high, vec!["Urgente"]);
+    assert_eq!(tracker.by_priority(Priority::Medium).count(), 1);
+    assert_eq!(tracker.by_priority(Priority::Low).count(), 0);
+}
+
+#[test]
+fn search_is_case_insensitive() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Proyecto Final".to_string(), "Entregar INFORME".to_string());
+    tracker.add_task("Compras".to_string(), "Leche y pan".to_string());
+
+    let titles = |query: &str| -> Vec<String> {
+        tracker.by_title_or_description(query).iter().map(|task| task.title.clone()).collect()
+    };
+
+    assert_eq!(titles("proyecto"), vec!["Proyecto Final"]);
+    assert_eq!(titles("PROYECTO "), vec!["Proyecto Final"]);
+    assert_eq!(titles("informe"), vec!["Proyecto Final"]);
+    assert_eq!(titles("LeChE"), vec!["Compras"]);
+    assert!(titles("tarea").is_empty());
+
+    // Una búsqueda vacía retorna todas las tareas
+    assert_eq!(titles("").len(), 2);
+    assert_eq!(titles("   ").len(), 2);
+}
+
+#[test]
+fn searchable_text_is_trimmed() {
+    // Las tareas restauradas de un archivo no pasan por la limpieza de Task::new
+    let now = Local::now().naive_utc();
+    let task = Task::restore(uuid::Uuid::new_v4(), "  Informe ".to_string(), "\n  Revisar el borrador  \n".to_string(), Status::ToDo, now, now);
+    assert_eq!(task.searchable_title(), "Informe");
+    assert_eq!(task.searchable_description(), "Revisar el borrador");
+
+    // La búsqueda y el resaltado trabajan sobre el mismo texto
+    assert!(task.matches("borrador"));
+    assert_eq!(split_matches(task.searchable_description(), "revisar"), vec![("Revisar", true), (" el borrador", false)]);
+    assert_eq!(task.score("informe"), Task::new("Informe", "Revisar el borrador").score("informe"));
+}
+
+#[test]
+fn visible_tasks_sorting() {
+    let mut tracker = temp_tracker();
+    for title in ["beta", "Alfa", "gamma"] {
+        tracker.add_task(title.to_string(), "Orden".to_string());
+    }
+
+    let titles = |tracker: &TaskTracker| -> Vec<String> {
+        tracker.visible_tasks().iter().map(|tv| tv.get_task().title.clone()).collect()
+    };
+
+    assert_eq!(titles(&tracker), vec!["beta", "Alfa", "gamma"]);
+
+    tracker.sort = SortBy::CreatedDesc;
+    assert_eq!(titles(&tracker), vec!["gamma", "Alfa", "beta"]);
+
+    tracker.sort = SortBy::TitleAsc;
+    assert_eq!(titles(&tracker), vec!["Alfa", "beta", "gamma"]);
+
+    // La tarea modificada más recientemente va primero
+    tracker.sort = SortBy::ModifiedDesc;
+    let id = tracker.get_tasks()[0].id();
+    tracker.update_task(id, None, Some("Editada".to_string()), None);
+    assert_eq!(titles(&tracker)[0], "beta");
+
+    // El orden se conserva junto con el filtro
+    tracker.filter.text = "orden".to_string();
+    tracker.sort = SortBy::TitleAsc;
+    assert_eq!(titles(&tracker), vec!["Alfa", "gamma"]);
+}
+
+#[test]
+fn tags_are_trimmed_and_deduplicated() {
+    assert_eq!(parse_tags(" casa, trabajo ,,casa, "), vec!["casa", "trabajo"]);
+
+    let mut task = Task::new("Test", "Etiquetas");
+    task.add_tag(" casa ");
+    task.add_tag("casa");
+    task.add_tag("");
+    task.add_tag("urgente");
+    assert_eq!(task.tags, vec!["casa", "urgente"]);
+
+    task.remove_tag("casa");
+    assert_eq!(task.tags, vec!["urgente"]);
+    assert!(task.has_tag("URGENTE"));
+}
+
+#[test]
+fn tracker_by_tag() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Limpiar".to_string(), "Cocina".to_string());
+    tracker.add_task("Informe".to_string(), "Mensual".to_string());
+
+    let id = tracker.get_tasks()[0].id();
+    tracker.get_task_mut(id).unwrap().set_tags(parse_tags("casa, hogar"));
+
+    let tagged: Vec<_> = tracker.by_tag("casa").map(|tv| tv.get_task().title.clone()).collect();
+    assert_eq!(tagged, vec!["Limpiar"]);
+
+    tracker.filter.tag = Some("hogar".to_string());
+    assert_eq!(tracker.visible_tasks().len(), 1);
+}
+
+#[test]
+fn delete_requires_confirmation() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Borrar".to_string(), "Confirmar".to_string());
+
+    let _ = tracker.tasks[0].update(task::Message::RequestDelete);
+    assert!(tracker.tasks[0].is_pending_delete());
+    assert_eq!(tracker.tasks.len(), 1);
+
+    // Escape o un click fuera cancela la eliminación
+    tracker.cancel_pending_deletes();
+    assert!(!tracker.tasks[0].is_pending_delete());
+}
+
+#[test]
+fn undo_delete_restores_task() {
+    let mut tracker = temp_tracker();
+    for title in ["Primera", "Segunda", "Tercera"] {
+        tracker.add_task(title.to_string(), "Deshacer".to_string());
+    }
+    let before: Vec<Task> = tracker.get_tasks().into_iter().cloned().collect();
+
+    let id = before[1].id();
+    tracker.remove_task(id);
+    assert_eq!(tracker.tasks.len(), 2);
+
+    tracker.undo_delete();
+    tracker.flush();
+    let after: Vec<Task> = tracker.get_tasks().into_iter().cloned().collect();
+    assert_eq!(before, after);
+    assert!(tracker.last_deleted.is_none());
+
+    // También se restaura en disco
+    let reloaded = TaskTracker::with_path(tracker.dir.join("tasks.json"));
+    assert_eq!(reloaded.get_tasks(), tracker.get_tasks());
+}
+
+#[test]
+fn settings_round_trip() {
+    let dir = temp_dir();
+    let path = dir.join("settings.json");
+
+    // Sin archivo se usan los valores por defecto
+    assert_eq!(load_settings(&path), Settings::default());
+
+    let settings = Settings {
+        preferences: Preferences { theme: Some(ThemeMode::Dark), warn_duplicates: true, ..Preferences::default() },
+        window: Some(WindowState { width: 600.0, height: 700.0, x: Some(10.0), y: Some(20.0) })
+    };
+    save_settings(&path, &settings).unwrap();
+    assert_eq!(load_settings(&path), settings);
+
+    // Una ventana inválida se descarta sin perder las preferencias
+    fs::write(&path, r#"{"preferences":{"theme":"dark"},"window":{"width":-1,"height":700}}"#).unwrap();
+    let loaded = load_settings(&path);
+    assert_eq!(loaded.window, None);
+    assert_eq!(loaded.preferences.theme, Some(ThemeMode::Dark));
+
+    // Un archivo inválido también usa los valores por defecto
+    fs::write(&path, "no es json").unwrap();
+    assert_eq!(load_settings(&path), Settings::default());
+}
+
+#[test]
+fn settings_import_legacy_files() {
+    let dir = temp_dir();
+    fs::write(dir.join("preferences.json"), r#"{"theme":"light","sort":"title-asc"}"#).unwrap();
+    fs::write(dir.join("window.json"), r#"{"width":600,"height":700,"x":null,"y":null}"#).unwrap();
+
+    let mut tracker = temp_tracker_in(dir);
+    assert_eq!(tracker.preferences.theme, Some(ThemeMode::Light));
+    assert_eq!(tracker.sort, SortBy::TitleAsc);
+    assert_eq!(tracker.window, Some(WindowState { width: 600.0, height: 700.0, x: None, y: None }));
+
+    // Al guardar se escribe todo en "settings.json", sin perder la ventana
+    tracker.save_preferences();
+    let settings = load_settings(&tracker.dir.join("settings.json"));
+    assert_eq!(settings.preferences.theme, Some(ThemeMode::Light));
+    assert_eq!(settings.window, tracker.window);
+}
+
+#[test]
+fn export_tasks_to_markdown() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.md");
+
+    let pending = Task::new("Comprar pan", "En la panadería");
+    let mut done = Task::new("Enviar informe", "Correo al equipo");
+    done.set_status(Status::Done);
+
+    export_markdown(&[&pending, &done], &path).unwrap();
+    let markdown = fs::read_to_string(&path).unwrap();
+
+    let headings: Vec<&str> = markdown.lines().filter(|line| line.starts_with("## ")).collect();
+    assert_eq!(headings, vec!["## Pendiente", "## En progreso", "## Terminada"]);
+
+    assert!(markdown.contains("- [ ] **Comprar pan**"));
+    assert!(markdown.contains("- [x] **Enviar informe**"));
+    assert!(markdown.contains("  En la panadería"));
+
+    // Cada tarea aparece bajo su encabezado
+    let todo_section = markdown.split("## En progreso").next().unwrap();
+    assert!(todo_section.contains("Comprar pan"));
+    assert!(!todo_section.contains("Enviar informe"));
+}
+
+#[test]
+fn jsonl_round_trip() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.jsonl");
+
+    let mut first = Task::new("Informe", "Línea 1\nLínea 2");
+    first.set_status(Status::InProgress);
+    first.set_tags(vec!["trabajo".to_string()]);
+    let second = Task::new("Compras", "Leche");
+
+    export_jsonl(&[&first, &second], &path).unwrap();
+    let contents = fs::read_to_string(&path).unwrap();
+    // Una tarea por línea, sin saltos de línea dentro del JSON
+    assert_eq!(contents.lines().count(), 2);
+
+    let (imported, errors) = import_jsonl(&path).unwrap();
+    assert!(errors.is_empty());
+    assert_eq!(imported, vec![first, second]);
+}
+
+#[test]
+fn jsonl_import_reports_malformed_lines() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.jsonl");
+
+    let first = Task::new("Informe", "Trabajo");
+    let second = Task::new("Compras", "Leche");
+    let contents = format!(
+        "{}\n\n{{\"title\": \"incompleta\"\n   \n{}\nno es json\n",
+        serde_json::to_string(&first).unwrap(),
+        serde_json::to_string(&second).unwrap()
+    );
+    fs::write(&path, contents).unwrap();
+
+    // Las líneas en blanco se omiten y las inválidas se informan con su número
+    let (imported, errors) = import_jsonl(&path).unwrap();
+    assert_eq!(imported, vec![first, second]);
+    assert_eq!(errors.iter().map(|error| error.line).collect::<Vec<_>>(), vec![3, 6]);
+    assert!(errors[0].to_string().starts_with("Error en la línea 3:"), "{}", errors[0]);
+
+    // Desde la interfaz se importan las válidas y se muestra la advertencia
+    let mut tracker = temp_tracker_in(dir);
+    let _ = tracker.update(Message::ImportJsonl);
+    assert_eq!(tracker.get_tasks().len(), 2);
+    assert!(tracker.warning.as_ref().unwrap().contains("Se omitieron 2 líneas"));
+}
+
+#[test]
+fn csv_round_trip() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.csv");
+
+    let mut first = Task::new("Informe, parte 1", "Línea 1\nLínea \"2\"");
+    first.set_status(Status::InProgress);
+    let second = Task::new("Compras", "Pan");
+
+    export_csv(&[&first, &second], &path).unwrap();
+    let imported = import_csv(&path).unwrap();
+
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported[0].id(), first.id());
+    assert_eq!(imported[0].title, first.title);
+    assert_eq!(imported[0].description, first.description);
+    assert_eq!(imported[0].status, Status::InProgress);
+    assert_eq!(imported[0].created_at(), first.created_at());
+    assert_eq!(imported[1].title, "Compras");
+    assert_eq!(imported[1].status, Status::ToDo);
+}
+
+#[test]
+fn csv_import_generates_missing_ids() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.csv");
+    fs::write(&path, "id,title,description,status,created_at,modified_at\n,Nueva,Desde hoja,Terminada,,\n").unwrap();
+
+    let imported = import_csv(&path).unwrap();
+    assert_eq!(imported.len(), 1);
+    assert!(!imported[0].id().is_nil());
+    assert_eq!(imported[0].status, Status::Done);
+
+    // Un estado inválido reporta la línea
+    fs::write(&path, "id,title,description,status,created_at,modified_at\n,Mala,Fila,desconocido,,\n").unwrap();
+    assert!(matches!(import_csv(&path), Err(crate::utils::CsvError::Field { line: 2, .. })));
+
+    // Una descripción con saltos de línea no desplaza la línea reportada
+    fs::write(&path, "id,title,description,status,created_at,modified_at\n,Larga,\"Uno\nDos\nTres\",Pendiente,,\n,Mala,Fila,desconocido,,\n").unwrap();
+    assert!(matches!(import_csv(&path), Err(crate::utils::CsvError::Field { line: 5, .. })));
+}
+
+#[test]
+fn status_display_has_no_newline() {
+    assert_eq!(Status::Done.to_string(), "Terminada");
+    assert_eq!(Status::InProgress.to_string(), "En progreso");
+    assert_eq!(Status::ToDo.to_string(), "Pendiente");
+}
+
+#[test]
+fn saves_are_debounced_until_flush() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+    let mut tracker = TaskTracker::with_path(path.clone());
+
+    tracker.add_task("Primera".to_string(), "Diferida".to_string());
+    tracker.add_task("Segunda".to_string(), "Diferida".to_string());
+    assert!(tracker.is_dirty());
+    assert!(read_tasks(&path).unwrap().is_empty());
+
+    // Una sola escritura guarda todos los cambios pendientes
+    tracker.flush();
+    assert!(!tracker.is_dirty());
+    assert_eq!(read_tasks(&path).unwrap().len(), 2);
+}
+
+#[test]
+fn completed_at_follows_done_status() {
+    let mut task = Task::new("Test", "Completar");
+    assert_eq!(task.completed_at(), None);
+
+    task.modify(None, None, Some(Status::Done));
+    let completed_at = task.completed_at().expect("debe registrarse al terminar");
+
+    // Volver a marcarla como terminada no cambia el momento de término
+    task.set_status(Status::Done);
+    assert_eq!(task.completed_at(), Some(completed_at));
+
+    task.set_status(Status::InProgress);
+    assert_eq!(task.completed_at(), None);
+}
+
+#[test]
+fn tracker_status_counts() {
+    let mut tracker = temp_tracker();
+    for title in ["Una", "Dos", "Tres", "Cuatro"] {
+        tracker.add_task(title.to_string(), "Contar".to_string());
+    }
+    assert_eq!(tracker.status_counts(), (4, 0, 0));
+
+    let ids: Vec<_> = tracker.get_tasks_iter().map(|task| task.id()).collect();
+    tracker.update_task(ids[0], None, None, Some(Status::Done));
+    tracker.update_task(ids[1], None, None, Some(Status::InProgress));
+    tracker.remove_task(ids[2]);
+    assert_eq!(tracker.status_counts(), (1, 1, 1));
+}
+
+#[test]
+fn move_task_changes_manual_order() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+    let mut tracker = TaskTracker::with_path(path.clone());
+    for title in ["A", "B", "C"] {
+        tracker.add_task(title.to_string(), "Mover".to_string());
+    }
+
+    let titles = |tracker: &TaskTracker| -> Vec<String> {
+        tracker.visible_tasks().iter().map(|tv| tv.get_task().title.clone()).collect()
+    };
+
+    let last = tracker.get_tasks()[2].id();
+    tracker.move_task(last, true);
+    assert_eq!(titles(&tracker), vec!["A", "C", "B"]);
+
+    // La primera no puede subir más
+    let first = tracker.get_tasks()[0].id();
+    tracker.move_task(first, true);
+    assert_eq!(titles(&tracker), vec!["A", "C", "B"]);
+
+    // El orden se conserva al recargar
+    tracker.flush();
+    let reloaded = TaskTracker::with_path(path);
+    assert_eq!(titles(&reloaded), vec!["A", "C", "B"]);
+
+    // Con una favorita primero, la vecina se busca por orden manual entre las que no son favoritas
+    let c = tracker.get_tasks()[2].id();
+    tracker.toggle_favorite(c);
+    assert_eq!(titles(&tracker), vec!["C", "A", "B"]);
+    let b = tracker.get_tasks()[1].id();
+    tracker.move_task(b, true);
+    assert_eq!(titles(&tracker), vec!["C", "B", "A"]);
+}
+
+#[test]
+fn long_descriptions_are_clamped() {
+    assert_eq!(clamp_text("Corta", 3, 180), None);
+    assert_eq!(clamp_text("1\n2\n3", 3, 180), None);
+    assert_eq!(clamp_text("1\n2\n3\n4", 3, 180), Some("1\n2\n3…".to_string()));
+    assert_eq!(clamp_text("abcdef", 3, 4), Some("abcd…".to_string()));
+}
+
+#[test]
+fn expanding_one_task_does_not_affect_others() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Primera".to_string(), "Larga".to_string());
+    tracker.add_task("Segunda".to_string(), "Larga".to_string());
+
+    let _ = tracker.tasks[0].update(task::Message::ToggleExpanded);
+    assert!(tracker.tasks[0].is_expanded());
+    assert!(!tracker.tasks[1].is_expanded());
+}
+
+#[test]
+fn create_from_form_uses_current_buffers() {
+    let mut tracker = temp_tracker();
+
+    // Con campos vacíos no se crea nada
+    let _ = tracker.update(Message::CreateFromForm);
+    assert!(tracker.tasks.is_empty());
+
+    tracker.title = "Atajo".to_string();
+    tracker.description = iced::widget::text_editor::Content::with_text("  Desde el teclado  ");
+    let _ = tracker.update(Message::CreateFromForm);
+
+    assert_eq!(tracker.tasks.len(), 1);
+    assert_eq!(tracker.get_tasks()[0].description, "Desde el teclado");
+    assert!(tracker.title.is_empty());
+}
+
+#[test]
+fn create_reports_missing_fields() {
+    let mut tracker = temp_tracker();
+
+    let _ = tracker.update(Message::Create("Solo título".to_string(), "  ".to_string()));
+    assert!(tracker.tasks.is_empty());
+    assert_eq!(tracker.form_errors, FormErrors { title: false, description: true });
+
+    let _ = tracker.update(Message::Create(" ".to_string(), "".to_string()));
+    assert_eq!(tracker.form_errors, FormErrors { title: true, description: true });
+
+    // Escribir un titulo válido limpia solo su error
+    let _ = tracker.update(Message::SetTitle("Título".to_string()));
+    assert_eq!(tracker.form_errors, FormErrors { title: false, description: true });
+
+    let _ = tracker.update(Message::Create("Título".to_string(), "Descripción".to_string()));
+    assert_eq!(tracker.tasks.len(), 1);
+}
+
+#[test]
+fn new_tasks_open_in_edit_when_enabled() {
+    let mut tracker = temp_tracker();
+
+    // Por defecto la tarea nueva queda en la vista estática
+    let _ = tracker.update(Message::Create("Primera".to_string(), "Uno".to_string()));
+    assert!(!tracker.tasks[0].is_editing());
+
+    let _ = tracker.update(Message::SetEditNewTasks(true));
+    let _ = tracker.update(Message::Create("Segunda".to_string(), "Dos".to_string()));
+    assert!(tracker.tasks[1].is_editing());
+    assert!(tracker.tasks[1].is_focused());
+    // El formulario se limpia para seguir creando tareas
+    assert!(tracker.title.is_empty());
+
+    // La siguiente tarea nueva cierra la anterior si no tenía cambios
+    let _ = tracker.update(Message::Create("Tercera".to_string(), "Tres".to_string()));
+    let editing: Vec<bool> = tracker.tasks.iter().map(TaskView::is_editing).collect();
+    assert_eq!(editing, vec![false, false, true]);
+    assert!(!tracker.tasks[1].is_focused());
+
+    // Una edición con cambios sin guardar se mantiene abierta
+    let id = tracker.tasks[2].get_task().id();
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::SetTitle("Tercera editada".to_string())));
+    let _ = tracker.update(Message::Create("Cuarta".to_string(), "Cuatro".to_string()));
+    let editing: Vec<bool> = tracker.tasks.iter().map(TaskView::is_editing).collect();
+    assert_eq!(editing, vec![false, false, true, true]);
+}
+
+#[test]
+fn fuzzy_search_finds_near_misses() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Proyecto final".to_string(), "Entregar el informe".to_string());
+    tracker.add_task("Compras".to_string(), "Leche y pan".to_string());
+    tracker.add_task("Gimnasio".to_string(), "Rutina de piernas".to_string());
+    let id = tracker.get_tasks()[2].id();
+    tracker.get_task_mut(id).unwrap().set_tags(vec!["salud".to_string()]);
+
+    let first = |query: &str| tracker.search(query).first().map(|task| task.title.clone());
+
+    // Errores de tipeo y letras omitidas
+    assert_eq!(first("proyceto").as_deref(), Some("Proyecto final"));
+    assert_eq!(first("pryecto").as_deref(), Some("Proyecto final"));
+    assert_eq!(first("infrome").as_deref(), Some("Proyecto final"));
+    // Palabras en otro orden
+    assert_eq!(first("final proyecto").as_deref(), Some("Proyecto final"));
+    // Etiquetas
+    assert_eq!(first("salud").as_deref(), Some("Gimnasio"));
+    assert!(tracker.search("xyzzy").is_empty());
+
+    // Las coincidencias textuales tienen prioridad sobre las aproximadas
+    let titles: Vec<String> = tracker.search("pan").iter().map(|task| task.title.clone()).collect();
+    assert_eq!(titles, vec!["Compras"]);
+
+    // La lista visible usa la misma búsqueda
+    tracker.filter.text = "gimansio".to_string();
+    assert_eq!(tracker.visible_tasks()[0].get_task().title, "Gimnasio");
+}
+
+#[test]
+fn load_distinguishes_missing_from_unreadable() {
+    let dir = temp_dir();
+
+    // Sin archivo: tracker vacío, sin error
+    let tracker = TaskTracker::load_from(dir.join("tasks.json")).unwrap();
+    assert!(tracker.tasks.is_empty());
+    assert!(tracker.warning.is_none());
+
+    // El archivo existe pero no se puede leer (aquí, un directorio en su lugar)
+    let unreadable = dir.join("unreadable.json");
+    fs::create_dir_all(&unreadable).unwrap();
+    let error = TaskTracker::load_from(unreadable.clone()).unwrap_err();
+    assert!(matches!(error.error, crate::utils::ReadError::Io(_)));
+    assert_eq!(error.path, unreadable);
+
+    // with_path inicia vacío mostrando la advertencia
+    let tracker = TaskTracker::with_path(unreadable);
+    assert!(tracker.tasks.is_empty());
+    assert!(tracker.warning.is_some());
+}
+
+#[test]
+fn reloads_external_changes_only() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+    let mut tracker = TaskTracker::with_path(path.clone());
+    tracker.add_task("Local".to_string(), "Propia".to_string());
+    tracker.flush();
+
+    // La escritura propia no provoca una recarga
+    tracker.check_for_changes();
+    assert_eq!(tracker.tasks.len(), 1);
+
+    // Una tarea en edición conserva su vista al recargar
+    let _ = tracker.tasks[0].update(task::Message::ToggleState);
+    let mut tasks = read_tasks(&path).unwrap();
+    tasks[0].set_title("Editada fuera".to_string());
+    tasks.push(Task::new("Externa", "Desde otro equipo"));
+    // Se fuerza una fecha de modificación distinta por si el sistema de archivos tiene poca resolución
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    write_tasks(&path, tasks.iter().collect()).unwrap();
+
+    tracker.check_for_changes();
+    let titles: Vec<String> = tracker.get_tasks_iter().map(|task| task.title.clone()).collect();
+    assert_eq!(titles, vec!["Editada fuera", "Externa"]);
+    assert!(tracker.tasks[0].is_editing());
+}
+
+#[test]
+fn toggle_subtask_done_flag() {
+    let mut task = Task::new("Mudanza", "Pasos");
+    task.add_subtask("Empacar");
+    task.add_subtask("  ");
+    task.add_subtask("Llamar al camión");
+    assert_eq!(task.subtask_progress(), (0, 2));
+
+    task.toggle_subtask(1);
+    assert!(task.subtasks[1].done);
+    assert_eq!(task.subtask_progress(), (1, 2));
+
+    task.toggle_subtask(1);
+    assert!(!task.subtasks[1].done);
+
+    // Un indice fuera de rango no hace nada
+    task.toggle_subtask(5);
+    task.remove_subtask(0);
+    assert_eq!(task.subtasks.len(), 1);
+
+    // Los archivos antiguos no tienen pasos
+    let json = serde_json::to_value(&task).unwrap();
+    let mut object = json.as_object().unwrap().clone();
+    object.remove("subtasks");
+    let des: Task = serde_json::from_value(serde_json::Value::Object(object)).unwrap();
+    assert!(des.subtasks.is_empty());
+}
+
+#[test]
+fn duplicate_task_copies_fields() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Reporte".to_string(), "Semanal".to_string());
+    let id = tracker.get_tasks()[0].id();
+    tracker.update_task(id, None, None, Some(Status::InProgress));
+    tracker.get_task_mut(id).unwrap().set_tags(vec!["trabajo".to_string()]);
+
+    let copy_id = tracker.duplicate_task(id).unwrap();
+    let original = tracker.get_task(id).unwrap();
+    let copy = tracker.get_task(copy_id).unwrap();
+
+    assert_ne!(copy.id(), original.id());
+    assert_eq!(copy.title, "Reporte (copia)");
+    assert_eq!(copy.description, original.description);
+    assert_eq!(copy.status, Status::InProgress);
+    assert_eq!(copy.tags, original.tags);
+    assert_eq!(copy.created_at(), copy.modified_at());
+    assert_eq!(tracker.tasks.len(), 2);
+}
+
+#[test]
+fn archived_tasks_are_hidden_from_normal_view() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Activa".to_string(), "Visible".to_string());
+    tracker.add_task("Vieja".to_string(), "Archivar".to_string());
+    let id = tracker.get_tasks()[1].id();
+    tracker.update_task(id, None, None, Some(Status::Done));
+
+    tracker.toggle_archive(id);
+    let titles = |tracker: &TaskTracker| -> Vec<String> {
+        tracker.visible_tasks().iter().map(|tv| tv.get_task().title.clone()).collect()
+    };
+    assert_eq!(titles(&tracker), vec!["Activa"]);
+    assert_eq!(tracker.status_counts(), (1, 0, 0));
+
+    tracker.filter.archived = true;
+    assert_eq!(titles(&tracker), vec!["Vieja"]);
+
+    tracker.toggle_archive(id);
+    assert!(titles(&tracker).is_empty());
+    assert_eq!(tracker.status_counts(), (1, 0, 1));
+}
+
+#[test]
+fn keyboard_edit_toggles_focused_task() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Primera".to_string(), "Uno".to_string());
+    tracker.add_task("Segunda".to_string(), "Dos".to_string());
+    let first = tracker.get_tasks()[0].id();
+    let second = tracker.get_tasks()[1].id();
+
+    // Sin tarea seleccionada no pasa nada
+    let _ = tracker.update(Message::ToggleFocusedEdit);
+    assert!(tracker.visible_tasks().into_iter().all(|tv| !tv.is_editing()));
+
+    let _ = tracker.update(Message::TaskMessage(first, task::Message::Focus));
+    let _ = tracker.update(Message::TaskMessage(second, task::Message::Focus));
+    let focused: Vec<bool> = tracker.visible_tasks().into_iter().map(|tv| tv.is_focused()).collect();
+    assert_eq!(focused, vec![false, true]);
+
+    let _ = tracker.update(Message::ToggleFocusedEdit);
+    assert!(tracker.visible_tasks().into_iter().any(|tv| tv.get_task().id() == second && tv.is_editing()));
+
+    // Un click en la lista vacía solo cancela las eliminaciones pendientes, no la edición
+    let _ = tracker.update(Message::TaskMessage(first, task::Message::RequestDelete));
+    let _ = tracker.update(Message::ClickAway);
+    assert!(tracker.visible_tasks().into_iter().all(|tv| !tv.is_pending_delete()));
+    assert!(tracker.visible_tasks().into_iter().any(|tv| tv.get_task().id() == second && tv.is_editing()));
+
+    // Escape en la búsqueda no sale de la edición
+    let editing = |tracker: &TaskTracker, id| tracker.tasks.iter().any(|tv| tv.get_task().id() == id && tv.is_editing());
+    let _ = tracker.update(Message::EscapeFocused(Some(crate::search_input_id().into())));
+    assert!(editing(&tracker, second));
+
+    // Escape sale solo de la edición de la tarea seleccionada; abrir otra para editar la selecciona
+    let _ = tracker.update(Message::TaskMessage(first, task::Message::ToggleState));
+    assert!(editing(&tracker, first) && editing(&tracker, second));
+    let _ = tracker.update(Message::Escape);
+    let _ = tracker.update(Message::EscapeFocused(None));
+    assert!(!editing(&tracker, first));
+    assert!(editing(&tracker, second));
+}
+
+#[test]
+fn cancel_edit_discards_field_changes() {
+    let mut task_view = TaskView::from(Task::new("Original", "Descripción original"));
+    let _ = task_view.update(task::Message::ToggleState);
+
+    let _ = task_view.update(task::Message::SetTitle("Cambiado".to_string()));
+    let _ = task_view.update(task::Message::SetStatus(Status::Done));
+    let _ = task_view.update(task::Message::SetDescription(text_editor::Action::Edit(text_editor::Edit::Insert('!'))));
+    assert_eq!(task_view.fields().title(), "Cambiado");
+
+    let _ = task_view.update(task::Message::CancelEdit);
+    assert!(!task_view.is_editing());
+
+    let _ = task_view.update(task::Message::ToggleState);
+    let task = task_view.get_task();
+    assert_eq!(task_view.fields().title(), task.title);
+    assert_eq!(task_view.fields().status(), task.status);
+    assert_eq!(task_view.fields().description(), task.description);
+}
+
+#[test]
+fn reset_fields_keeps_editor_cursor_while_text_matches() {
+    let mut task_view = TaskView::from(Task::new("Notas", "Primera línea\nSegunda línea\nTercera"));
+    let _ = task_view.update(task::Message::ToggleState);
+    let _ = task_view.update(task::Message::SetDescription(text_editor::Action::Move(text_editor::Motion::Down)));
+    let _ = task_view.update(task::Message::SetDescription(text_editor::Action::Move(text_editor::Motion::End)));
+    let position = task_view.fields().cursor_position();
+    assert_ne!(position, (0, 0));
+
+    // Salir y volver a editar sin cambios conserva el cursor
+    let _ = task_view.update(task::Message::CancelEdit);
+    let _ = task_view.update(task::Message::ToggleState);
+    assert_eq!(task_view.fields().cursor_position(), position);
+
+    // Si la descripción de la tarea cambió, el editor se vuelve a crear
+    task_view.get_task_mut().description = "Otro texto".to_string();
+    task_view.reset_fields();
+    assert_eq!(task_view.fields().cursor_position(), (0, 0));
+    assert_eq!(task_view.fields().description(), "Otro texto");
+}
+
+#[test]
+fn text_stats_counts_words_and_chars() {
+    assert_eq!(text_stats(""), (0, 0));
+    // El editor siempre agrega un salto de línea final
+    assert_eq!(text_stats("Hola mundo\n"), (2, 10));
+    assert_eq!(text_stats("Línea uno\n  línea dos"), (4, 21));
+}
+
+#[test]
+fn bulk_set_status_only_changes_visible_tasks() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+    let mut tracker = TaskTracker::with_path(path.clone());
+    tracker.add_task("Informe semanal".to_string(), "Sprint".to_string());
+    tracker.add_task("Informe mensual".to_string(), "Sprint".to_string());
+    tracker.add_task("Comprar pan".to_string(), "Casa".to_string());
+
+    tracker.flush();
+
+    // Sin coincidencias no se hace nada
+    tracker.filter.text = "zzzzzz".to_string();
+    assert_eq!(tracker.bulk_set_status(Status::Done), 0);
+    assert!(!tracker.is_dirty());
+
+    tracker.filter.text = "informe".to_string();
+    assert_eq!(tracker.bulk_set_status(Status::Done), 2);
+    assert_eq!(tracker.status_counts(), (1, 0, 2));
+    assert!(tracker.get_tasks_iter().filter(|task| task.status == Status::Done).all(|task| task.completed_at().is_some()));
+
+    tracker.flush();
+    let done = read_tasks(&path).unwrap().into_iter().filter(|task| task.status == Status::Done).count();
+    assert_eq!(done, 2);
+}
+
+#[test]
+fn format_relative_time_deltas() {
+    let now = NaiveDate::from_ymd_opt(2024, 5, 20).unwrap().and_hms_opt(12, 0, 0).unwrap();
+    let ago = |delta: TimeDelta| format_relative_to(now - delta, now, DEFAULT_DATE_TIME_FORMAT, DEFAULT_LOCALE);
+
+    assert_eq!(ago(TimeDelta::seconds(10)), "justo ahora");
+    assert_eq!(ago(TimeDelta::minutes(1)), "hace 1 minuto");
+    assert_eq!(ago(TimeDelta::minutes(5)), "hace 5 minutos");
+    assert_eq!(ago(TimeDelta::hours(2)), "hace 2 horas");
+    assert_eq!(ago(TimeDelta::hours(30)), "ayer");
+    assert_eq!(ago(TimeDelta::days(3)), "hace 3 días");
+
+    // Más de una semana, o en el futuro, usa el formato completo
+    assert_eq!(ago(TimeDelta::days(10)), format_timestamp(now - TimeDelta::days(10), DEFAULT_LOCALE));
+    assert_eq!(ago(TimeDelta::hours(-1)), format_timestamp(now + TimeDelta::hours(1), DEFAULT_LOCALE));
+}
+
+#[test]
+fn timestamps_are_stored_in_utc() {
+    let utc = NaiveDate::from_ymd_opt(2024, 5, 20).unwrap().and_hms_opt(12, 0, 0).unwrap();
+
+    // Las fechas con zona horaria se convierten a UTC
+    assert_eq!(utc_timestamp::parse("2024-05-20T12:00:00Z").unwrap(), utc);
+    assert_eq!(utc_timestamp::parse("2024-05-20T17:00:00+05:00").unwrap(), utc);
+
+    // El formato antiguo, sin zona, se interpreta como hora local
+    let local = utc_to_local(utc);
+    let legacy = local.format("%Y-%m-%dT%H:%M:%S").to_string();
+    assert_eq!(utc_timestamp::parse(&legacy).unwrap(), utc);
+
+    // Al mostrarse se usa la hora local
+    assert_eq!(local, Local.from_utc_datetime(&utc).naive_local());
+    assert_eq!(format_timestamp(utc, DEFAULT_LOCALE), format_date_time(local, DEFAULT_LOCALE));
+
+    // Se guarda con sufijo Z y se vuelve a leer sin desplazarse
+    let task = Task::new("UTC", "Zona horaria");
+    let json = serde_json::to_string(&task).unwrap();
+    assert!(json.contains(&format!("\"created_at\":\"{}Z\"", task.created_at().format("%Y-%m-%dT%H:%M:%S%.f"))));
+    assert_eq!(serde_json::from_str::<Task>(&json).unwrap(), task);
+}
+
+#[test]
+fn format_date_time_uses_locale() {
+    let date_time = NaiveDate::from_ymd_opt(2024, 5, 20).unwrap().and_hms_opt(12, 0, 0).unwrap();
+
+    let spanish = format_date_time(date_time, Locale::es_PE);
+    let french = format_date_time(date_time, Locale::fr_FR);
+    assert!(spanish.starts_with("lunes 20 de mayo del 2024"), "{}", spanish);
+    assert!(french.starts_with("lundi 20 de mai del 2024"), "{}", french);
+    assert_ne!(spanish, french);
+}
+
+#[test]
+fn format_date_time_falls_back_on_invalid_format() {
+    let date_time = NaiveDate::from_ymd_opt(2024, 5, 20).unwrap().and_hms_opt(12, 30, 5).unwrap();
+
+    assert_eq!(
+        try_format_localized(date_time, "%A %d de %B del %Y", "%r", Locale::es_PE),
+        Ok(format_date_time(date_time, Locale::es_PE))
+    );
+    // Un especificador desconocido es un error, no un pánico
+    assert!(try_format_localized(date_time, "%Q", "%r", Locale::es_PE).is_err());
+
+    // El formato de respaldo siempre se puede volver a leer
+    let fallback = date_time.format(FALLBACK_DATE_TIME_FORMAT).to_string();
+    assert_eq!(fallback, "2024-05-20 12:30:05");
+    assert_eq!(chrono::NaiveDateTime::parse_from_str(&fallback, FALLBACK_DATE_TIME_FORMAT), Ok(date_time));
+}
+
+#[test]
+fn duplicate_titles_need_confirmation_when_enabled() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Pagar luz".to_string(), "Recibo".to_string());
+
+    assert!(tracker.has_duplicate_title("  pagar LUZ "));
+    assert!(!tracker.has_duplicate_title("Pagar agua"));
+
+    // Desactivado por defecto, se crea sin preguntar
+    let _ = tracker.update(Message::Create("Pagar luz".to_string(), "Otra vez".to_string()));
+    assert_eq!(tracker.get_tasks().len(), 2);
+
+    tracker.preferences.warn_duplicates = true;
+    let _ = tracker.update(Message::Create("PAGAR LUZ".to_string(), "Tercera".to_string()));
+    assert_eq!(tracker.get_tasks().len(), 2);
+    assert!(tracker.pending_create.is_some());
+
+    let _ = tracker.update(Message::ConfirmCreate);
+    assert_eq!(tracker.get_tasks().len(), 3);
+    assert!(tracker.pending_create.is_none());
+}
+
+#[test]
+fn split_matches_marks_every_occurrence() {
+    assert_eq!(split_matches("Informe", ""), vec![("Informe", false)]);
+    assert_eq!(split_matches("Informe", "xyz"), vec![("Informe", false)]);
+    assert_eq!(split_matches("", "xyz"), vec![("", false)]);
+
+    assert_eq!(
+        split_matches("Leer el INFORME y archivar el informe", "informe"),
+        vec![("Leer el ", false), ("INFORME", true), (" y archivar el ", false), ("informe", true)]
+    );
+    // Cada palabra de la búsqueda se resalta por separado, también con acentos
+    assert_eq!(
+        split_matches("Reunión de equipo", "equipo REUNIÓN"),
+        vec![("Reunión", true), (" de ", false), ("equipo", true)]
+    );
+}
+
+#[test]
+fn editor_height_grows_up_to_the_max() {
+    let height = |text: &str| task::editor_height(&text_editor::Content::with_text(text));
+
+    assert_eq!(height(""), task::EDITOR_MIN_HEIGHT);
+    assert_eq!(height("Una línea"), task::EDITOR_MIN_HEIGHT);
+    let medium = height(&"línea\n".repeat(6));
+    assert!(medium > task::EDITOR_MIN_HEIGHT && medium < task::EDITOR_MAX_HEIGHT, "{}", medium);
+    assert_eq!(height(&"línea\n".repeat(50)), task::EDITOR_MAX_HEIGHT);
+}
+
+#[test]
+fn parse_emphasis_handles_simple_markdown() {
+    let bold = Emphasis { bold: true, ..Emphasis::default() };
+    let italic = Emphasis { italic: true, ..Emphasis::default() };
+    let code = Emphasis { code: true, ..Emphasis::default() };
+    let plain = |text: &str| (text.to_string(), Emphasis::default());
+    let styled = |text: &str, emphasis| (text.to_string(), emphasis);
+
+    assert_eq!(parse_emphasis(""), vec![]);
+    assert_eq!(parse_emphasis("Sin formato\nen dos líneas"), vec![plain("Sin formato\nen dos líneas")]);
+    assert_eq!(
+        parse_emphasis("Revisar **todo** el *informe* con `cargo test`"),
+        vec![plain("Revisar "), styled("todo", bold), plain(" el "), styled("informe", italic), plain(" con "), styled("cargo test", code)]
+    );
+    // El énfasis se puede anidar, pero dentro del código no se interpreta
+    assert_eq!(
+        parse_emphasis("**muy *importante***"),
+        vec![styled("muy ", bold), styled("importante", Emphasis { bold: true, italic: true, code: false })]
+    );
+    assert_eq!(parse_emphasis("`**x**`"), vec![styled("**x**", code)]);
+    // Los delimitadores sueltos se dejan tal cual
+    assert_eq!(parse_emphasis("2 * 3 = 6 y **sin cerrar"), vec![plain("2 * 3 = 6 y **sin cerrar")]);
+}
+
+#[test]
+fn filter_and_sort_are_restored_on_startup() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+
+    let mut tracker = TaskTracker::with_path(path.clone());
+    tracker.filter.text = "informe".to_string();
+    tracker.filter.status = StatusFilter::only(Status::InProgress);
+    tracker.sort = SortBy::TitleAsc;
+    tracker.save_view_state();
+
+    let tracker = TaskTracker::with_path(path.clone());
+    assert_eq!(tracker.filter.text, "informe");
+    assert_eq!(tracker.filter.status, StatusFilter::only(Status::InProgress));
+    assert_eq!(tracker.sort, SortBy::TitleAsc);
+    // Se guarda aparte del archivo de tareas
+    assert!(read_tasks(&path).unwrap().is_empty());
+
+    // Un estado u orden desconocido vuelve a "Todas" sin perder el resto
+    let settings = dir.join("settings.json");
+    fs::write(&settings, r#"{"preferences":{"theme":"dark","filter_text":"x","filter_status":"blocked","sort":"random"}}"#).unwrap();
+    let tracker = TaskTracker::with_path(path);
+    assert_eq!(tracker.filter.status, StatusFilter::default());
+    assert_eq!(tracker.sort, SortBy::Manual);
+    assert_eq!(tracker.filter.text, "x");
+    assert_eq!(tracker.preferences.theme, Some(ThemeMode::Dark));
+}
+
+#[test]
+fn page_follows_filter_and_sort() {
+    let mut tracker = temp_tracker();
+    for i in 0..7 {
+        tracker.add_task(format!("Tarea {}", i), "Descripción".to_string());
+    }
+    tracker.add_task("Otra".to_string(), "Sin número".to_string());
+    tracker.filter.text = "tarea".to_string();
+    tracker.sort = SortBy::TitleAsc;
+
+    let titles = |offset, limit| -> Vec<String> {
+        tracker.page(offset, limit).iter().map(|task| task.title.clone()).collect()
+    };
+
+    // Primera página
+    assert_eq!(titles(0, 3), vec!["Tarea 0", "Tarea 1", "Tarea 2"]);
+    // Última página incompleta
+    assert_eq!(titles(6, 3), vec!["Tarea 6"]);
+    // Fuera de rango
+    assert!(titles(7, 3).is_empty());
+    assert!(titles(usize::MAX, usize::MAX).is_empty());
+    assert_eq!(titles(5, usize::MAX), vec!["Tarea 5", "Tarea 6"]);
+    assert!(titles(0, 0).is_empty());
+}
+
+#[test]
+fn visible_and_total_counts_follow_filter() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Informe semanal".to_string(), "Trabajo".to_string());
+    tracker.add_task("Informe mensual".to_string(), "Trabajo".to_string());
+    tracker.add_task("Comprar pan".to_string(), "Casa".to_string());
+    let id = tracker.get_tasks()[0].id();
+    tracker.update_task(id, None, None, Some(Status::Done));
+
+    assert_eq!((tracker.visible_count(), tracker.total_count()), (3, 3));
+
+    tracker.filter.text = "informe".to_string();
+    assert_eq!(tracker.visible_count(), 2);
+
+    tracker.filter.status = StatusFilter::only(Status::ToDo);
+    assert_eq!(tracker.visible_count(), 1);
+    assert_eq!(tracker.visible_count(), tracker.visible_tasks().len());
+    assert_eq!(tracker.total_count(), 3);
+
+    // Una búsqueda aproximada, sin coincidencias exactas, cuenta lo mismo que se muestra
+    tracker.filter.status = StatusFilter::default();
+    for query in ["infrme", "pan", "zzz"] {
+        tracker.filter.text = query.to_string();
+        assert_eq!(tracker.visible_count(), tracker.visible_tasks().len(), "{}", query);
+    }
+}
+
+#[test]
+fn duplicate_ids_are_regenerated_on_load() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+    let original = Task::new("Original", "Primera");
+    let mut copy = original.clone();
+    copy.title = "Copia".to_string();
+    write_tasks(&path, vec![&original, &copy]).unwrap();
+
+    let mut tracker = TaskTracker::with_path(path.clone());
+    let ids: Vec<_> = tracker.get_tasks_iter().map(|task| task.id()).collect();
+    assert_eq!(ids.len(), 2);
+    assert_ne!(ids[0], ids[1]);
+    // La primera aparición conserva su id
+    assert_eq!(tracker.get_task(original.id()).unwrap().title, "Original");
+    assert!(tracker.warning.is_some());
+
+    // Los ids corregidos se guardan
+    tracker.flush();
+    let saved: Vec<_> = read_tasks(&path).unwrap().iter().map(|task| task.id()).collect();
+    assert_eq!(saved, ids);
+}
+
+#[test]
+fn task_color_round_trips() {
+    let mut task = Task::new("Con color", "Azul");
+    assert_eq!(task.color, None);
+    task.set_color(Some(task::COLORS[4]));
+
+    let ser = serde_json::to_string(&task).unwrap();
+    let de: Task = serde_json::from_str(&ser).unwrap();
+    assert_eq!(de.color, Some([52, 152, 219]));
+
+    // Los archivos sin el campo quedan sin color
+    let ser = ser.replace(",\"color\":[52,152,219]", "");
+    assert!(!ser.contains("\"color\""));
+    assert_eq!(serde_json::from_str::<Task>(&ser).unwrap().color, None);
+}
+
+#[test]
+fn expand_and_collapse_all_tasks() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Primera".to_string(), "Uno".to_string());
+    tracker.add_task("Segunda".to_string(), "Dos".to_string());
+    tracker.flush();
+
+    let _ = tracker.update(Message::SetAllExpanded(true));
+    assert!(tracker.visible_tasks().iter().all(|tv| tv.is_expanded()));
+    // Es solo estado de la interfaz
+    assert!(!tracker.is_dirty());
+
+    let _ = tracker.update(Message::SetAllExpanded(false));
+    assert!(tracker.visible_tasks().iter().all(|tv| !tv.is_expanded()));
+}
+
+#[test]
+fn long_titles_are_truncated() {
+    assert_eq!(truncate_chars("Corto", 10), "Corto");
+    assert_eq!(truncate_chars("Demasiado largo", 10), "Demasiado…");
+
+    let long = "á".repeat(task::MAX_TITLE_CHARS + 20);
+    let mut task = Task::new("Título", "Descripción");
+    task.set_title(long.clone());
+    assert_eq!(task.title.chars().count(), task::MAX_TITLE_CHARS);
+    assert_eq!(task.title, format!("{}…", "á".repeat(task::MAX_TITLE_CHARS - 1)));
+
+    task.modify(Some(long.clone()), None, None);
+    assert_eq!(task.title.chars().count(), task::MAX_TITLE_CHARS);
+    assert_eq!(Task::new(long.as_str(), "Descripción").title, task.title);
+}
+
+#[test]
+fn clear_completed_archives_only_done_tasks() {
+    let mut tracker = temp_tracker();
+
+    // Sin terminadas no hace nada
+    assert_eq!(tracker.clear_completed(), 0);
+
+    for (title, status) in [("Uno", Status::Done), ("Dos", Status::ToDo), ("Tres", Status::InProgress), ("Cuatro", Status::Done)] {
+        tracker.add_task(title.to_string(), "Tarea".to_string());
+        let id = tracker.get_tasks().last().unwrap().id();
+        tracker.update_task(id, None, None, Some(status));
+    }
+
+    assert_eq!(tracker.clear_completed(), 2);
+    assert_eq!(tracker.status_counts(), (1, 1, 0));
+    let titles: Vec<String> = tracker.visible_tasks().iter().map(|tv| tv.get_task().title.clone()).collect();
+    assert_eq!(titles, vec!["Dos", "Tres"]);
+    assert_eq!(tracker.archived_tasks().count(), 2);
+    assert_eq!(tracker.clear_completed(), 0);
+}
+
+#[test]
+fn keyboard_selection_follows_visible_order() {
+    let mut tracker = temp_tracker();
+    for title in ["Cebolla", "Ajo", "Berenjena"] {
+        tracker.add_task(title.to_string(), "Verdura".to_string());
+    }
+    tracker.sort = SortBy::TitleAsc;
+
+    let selected = |tracker: &TaskTracker| -> Option<String> {
+        tracker.visible_tasks().iter().find(|tv| tv.is_focused()).map(|tv| tv.get_task().title.clone())
+    };
+
+    let _ = tracker.update(Message::SelectNext);
+    assert_eq!(selected(&tracker).as_deref(), Some("Ajo"));
+    let _ = tracker.update(Message::SelectNext);
+    let _ = tracker.update(Message::SelectNext);
+    let _ = tracker.update(Message::SelectNext);
+    assert_eq!(selected(&tracker).as_deref(), Some("Cebolla"));
+    let _ = tracker.update(Message::SelectPrev);
+    assert_eq!(selected(&tracker).as_deref(), Some("Berenjena"));
+
+    // Las flechas no cambian la selección si un campo de texto tiene el foco
+    let _ = tracker.update(Message::ArrowSelectFocused(true, true));
+    assert_eq!(selected(&tracker).as_deref(), Some("Berenjena"));
+    let _ = tracker.update(Message::ArrowSelectFocused(false, false));
+    assert_eq!(selected(&tracker).as_deref(), Some("Ajo"));
+    let _ = tracker.update(Message::ArrowSelectFocused(true, false));
+
+    let _ = tracker.update(Message::RequestDeleteFocused);
+    assert!(tracker.visible_tasks().iter().any(|tv| tv.is_focused() && tv.is_pending_delete()));
+}
+
+#[test]
+fn time_in_progress_accumulates() {
+    let start = NaiveDate::from_ymd_opt(2024, 5, 20).unwrap().and_hms_opt(9, 0, 0).unwrap();
+    let mut task = Task::new("Cronometrada", "Tiempo");
+    assert_eq!(task.time_in_progress(start), Duration::ZERO);
+
+    task.set_status_at(Status::InProgress, start);
+    // Mientras sigue en progreso se cuenta el periodo actual
+    assert_eq!(task.time_in_progress(start + TimeDelta::minutes(10)), Duration::from_secs(600));
+
+    task.set_status_at(Status::ToDo, start + TimeDelta::minutes(50));
+    assert_eq!(task.time_in_progress(start + TimeDelta::hours(5)), Duration::from_secs(50 * 60));
+
+    task.set_status_at(Status::InProgress, start + TimeDelta::hours(6));
+    task.set_status_at(Status::Done, start + TimeDelta::hours(6) + TimeDelta::minutes(30));
+    let total = task.time_in_progress(start + TimeDelta::days(1));
+    assert_eq!(total, Duration::from_secs(80 * 60));
+    assert_eq!(format_duration(total), "1h 20m");
+    assert_eq!(format_duration(Duration::from_secs(59)), "menos de 1m");
+
+    // Se guarda en segundos y se restaura
+    let json = serde_json::to_string(&task).unwrap();
+    assert!(json.contains("\"total_in_progress\":4800"));
+    assert_eq!(serde_json::from_str::<Task>(&json).unwrap(), task);
+}
+
+#[test]
+fn merge_tasks_keeps_latest_edit() {
+    let mut shared = Task::new("Compartida", "Original");
+    let local_only = Task::new("Local", "Solo aquí");
+    let remote_only = Task::new("Remota", "Solo allá");
+
+    let mut remote_edit = shared.clone();
+    remote_edit.set_title("Editada en otra máquina".to_string());
+    remote_edit.modified_at = shared.modified_at + TimeDelta::minutes(5);
+    shared.set_description("Editada antes aquí".to_string());
+
+    let merged = merge_tasks(vec![shared.clone(), local_only.clone()], vec![remote_edit.clone(), remote_only.clone()]);
+    assert_eq!(merged, vec![remote_edit.clone(), local_only, remote_only]);
+
+    // En un empate o si la local es más reciente, gana la local
+    let mut newer = remote_edit.clone();
+    newer.modified_at += TimeDelta::minutes(1);
+    assert_eq!(merge_tasks(vec![newer.clone()], vec![remote_edit.clone()]), vec![newer]);
+    assert_eq!(merge_tasks(vec![remote_edit.clone()], vec![remote_edit.clone()]), vec![remote_edit]);
+}
+
+#[test]
+fn flush_merges_external_changes() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+    let mut tracker = TaskTracker::with_path(path.clone());
+    tracker.add_task("Borrar".to_string(), "Se elimina aquí".to_string());
+    tracker.add_task("Conservar".to_string(), "Sigue".to_string());
+    tracker.flush();
+
+    // Cambios locales sin guardar mientras otra máquina agrega una tarea
+    tracker.add_task("Local".to_string(), "Nueva aquí".to_string());
+    let deleted = tracker.get_tasks()[0].id();
+    tracker.remove_task(deleted);
+
+    let mut remote = read_tasks(&path).unwrap();
+    remote.push(Task::new("Remota", "Nueva allá"));
+    write_tasks(&path, remote.iter().collect()).unwrap();
+
+    tracker.flush();
+    let mut titles: Vec<String> = read_tasks(&path).unwrap().into_iter().map(|task| task.title).collect();
+    titles.sort();
+    assert_eq!(titles, vec!["Conservar", "Local", "Remota"]);
+}
+
+#[test]
+fn flush_keeps_local_favorite_over_older_external_edit() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Informe".to_string(), "Original".to_string());
+    tracker.flush();
+    let id = tracker.get_tasks()[0].id();
+    let path = tracker.path.clone();
+
+    // Otra máquina edita la tarea y después aquí se marca como favorita
+    let mut remote = read_tasks(&path).unwrap();
+    remote[0].set_description("Editada fuera".to_string());
+    remote[0].modified();
+    write_tasks(&path, remote.iter().collect()).unwrap();
+    tracker.toggle_favorite(id);
+
+    tracker.flush();
+    assert!(read_tasks(&path).unwrap()[0].favorite);
+}
+
+#[test]
+fn migrate_versioned_and_legacy_files() {
+    let task = Task::new("Versionada", "Formato");
+
+    // Versión 0: arreglo sin envoltorio
+    let legacy = serde_json::to_value(vec![&task]).unwrap();
+    assert_eq!(migrate(legacy).unwrap(), vec![task.clone()]);
+
+    // Versión 1
+    let current = serde_json::json!({ "version": 1, "tasks": [&task] });
+    assert_eq!(migrate(current).unwrap(), vec![task.clone()]);
+
+    // Versiones desconocidas o formatos inválidos son un error
+    assert!(migrate(serde_json::json!({ "version": 99, "tasks": [] })).is_err());
+    assert!(migrate(serde_json::json!({ "tasks": [] })).is_err());
+    assert!(migrate(serde_json::json!("tareas")).is_err());
+
+    // Siempre se escribe la versión actual
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+    write_tasks(&path, vec![&task]).unwrap();
+    let written: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(written["version"], 1);
+    assert_eq!(read_tasks(&path).unwrap(), vec![task]);
+}
+
+#[test]
+fn newer_or_damaged_files_are_not_overwritten() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+
+    // Un archivo de una versión más nueva se deja intacto y no se guarda sobre él
+    let newer = r#"{ "version": 99, "tasks": [] }"#;
+    fs::write(&path, newer).unwrap();
+    assert!(matches!(read_tasks(&path), Err(crate::utils::ReadError::Version(99))));
+
+    let mut tracker = temp_tracker_in(dir);
+    assert!(tracker.warning.is_some());
+    tracker.add_task("Nueva".to_string(), "No se guarda".to_string());
+    tracker.flush();
+    assert!(!tracker.is_dirty());
+    assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+    assert!(!tracker.dir.join("tasks.json.bak").exists());
+
+    // Un archivo dañado se mueve a ".bak" sin reemplazar una copia anterior
+    fs::write(&path, "dañado 1").unwrap();
+    assert!(matches!(read_tasks(&path), Err(crate::utils::ReadError::Parse(_))));
+    fs::write(&path, "dañado 2").unwrap();
+    assert!(read_tasks(&path).is_err());
+
+    let mut backups: Vec<String> = fs::read_dir(&*tracker.dir).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "bak"))
+        .map(|path| fs::read_to_string(path).unwrap())
+        .collect();
+    backups.sort();
+    assert_eq!(backups, ["dañado 1", "dañado 2"]);
+}
+
+#[test]
+fn closing_with_unsaved_input_asks_for_confirmation() {
+    let mut tracker = temp_tracker();
+    let window = iced::window::Id::unique();
+    assert!(!tracker.has_unsaved_input());
+
+    let _ = tracker.update(Message::SetTitle("Borrador".to_string()));
+    assert!(tracker.has_unsaved_input());
+    let _ = tracker.update(Message::CloseRequested(window));
+    assert_eq!(tracker.pending_close, Some(window));
+
+    let _ = tracker.update(Message::CancelClose);
+    assert_eq!(tracker.pending_close, None);
+
+    // Una tarea en edición también cuenta
+    let _ = tracker.update(Message::SetTitle(String::new()));
+    tracker.add_task("Tarea".to_string(), "Descripción".to_string());
+    let id = tracker.get_tasks()[0].id();
+    assert!(!tracker.has_unsaved_input());
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::ToggleState));
+    assert!(tracker.has_unsaved_input());
+}
+
+#[test]
+fn dependencies_block_tasks_and_reject_cycles() {
+    let mut tracker = temp_tracker();
+    for title in ["Diseño", "Desarrollo", "Pruebas"] {
+        tracker.add_task(title.to_string(), "Proyecto".to_string());
+    }
+    let ids: Vec<_> = tracker.get_tasks_iter().map(|task| task.id()).collect();
+    let (design, develop, test) = (ids[0], ids[1], ids[2]);
+
+    assert!(!tracker.is_blocked(develop));
+    assert!(tracker.add_dependency(develop, design));
+    assert!(tracker.add_dependency(test, develop));
+    assert!(tracker.is_blocked(develop));
+    assert!(tracker.is_blocked(test));
+
+    // Ciclos directos, indirectos y consigo misma
+    assert!(!tracker.add_dependency(design, develop));
+    assert!(!tracker.add_dependency(design, test));
+    assert!(!tracker.add_dependency(design, design));
+    assert!(tracker.get_task(design).unwrap().depends_on.is_empty());
+    let options: Vec<_> = tracker.dependency_options(design).into_iter().map(|option| option.id).collect();
+    assert!(options.is_empty());
+
+    tracker.update_task(design, None, None, Some(Status::Done));
+    assert!(!tracker.is_blocked(develop));
+    assert!(tracker.is_blocked(test));
+
+    // Una dependencia eliminada ya no bloquea
+    tracker.remove_task(develop);
+    assert!(!tracker.is_blocked(test));
+}
+
+#[test]
+fn sync_from_tasks_keeps_unchanged_views() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Igual".to_string(), "Sin cambios".to_string());
+    tracker.add_task("Cambia".to_string(), "Antes".to_string());
+    tracker.add_task("Se va".to_string(), "Eliminada fuera".to_string());
+    let ids: Vec<_> = tracker.get_tasks_iter().map(|task| task.id()).collect();
+
+    // Estado de la interfaz que se perdería si se reconstruyera la vista
+    tracker.set_all_expanded(true);
+    tracker.focus_task(ids[0]);
+
+    let mut tasks: Vec<Task> = tracker.get_tasks().into_iter().cloned().collect();
+    tasks[1].set_description("Después".to_string());
+    tasks.remove(2);
+    tasks.push(Task::new("Nueva", "Agregada fuera"));
+    tracker.sync_from_tasks(tasks);
+
+    let views = &tracker.tasks;
+    assert_eq!(views.len(), 3);
+    assert!(views[0].is_expanded() && views[0].is_focused());
+    assert!(views[1].is_expanded());
+    assert_eq!(views[1].fields().description(), "Después");
+    assert!(!views[2].is_expanded());
+    assert!(tracker.get_task(ids[2]).is_none());
+
+    // Con los mismos datos la vista no se reemplaza: se conserva incluso un borrado pendiente
+    let _ = tracker.update(Message::TaskMessage(ids[0], task::Message::RequestDelete));
+    let same: Vec<Task> = tracker.get_tasks().into_iter().cloned().collect();
+    tracker.sync_from_tasks(same);
+    assert!(tracker.tasks[0].is_pending_delete());
+    assert!(tracker.tasks[0].is_expanded() && tracker.tasks[0].is_focused());
+}
+
+#[test]
+fn completion_ratio_ignores_archived_tasks() {
+    let mut tracker = temp_tracker();
+    assert_eq!(tracker.completion_ratio(), 0.0);
+
+    for (title, status) in [("Uno", Status::Done), ("Dos", Status::ToDo), ("Tres", Status::InProgress), ("Cuatro", Status::Done), ("Cinco", Status::Done)] {
+        tracker.add_task(title.to_string(), "Tarea".to_string());
+        let id = tracker.get_tasks().last().unwrap().id();
+        tracker.update_task(id, None, None, Some(status));
+    }
+    assert_eq!(tracker.completion_ratio(), 0.6);
+
+    // Las archivadas no cuentan en el total
+    let id = tracker.get_tasks()[0].id();
+    tracker.toggle_archive(id);
+    assert_eq!(tracker.completion_ratio(), 0.5);
+}
+
+#[test]
+fn status_filter_combines_statuses() {
+    let mut tracker = temp_tracker();
+    for (title, status) in [("Uno", Status::ToDo), ("Dos", Status::InProgress), ("Tres", Status::Done)] {
+        tracker.add_task(title.to_string(), "Tarea".to_string());
+        let id = tracker.get_tasks().last().unwrap().id();
+        tracker.update_task(id, None, None, Some(status));
+    }
+    let titles = |tracker: &TaskTracker| {
+        let mut titles: Vec<String> = tracker.visible_tasks().iter().map(|tv| tv.get_task().title.clone()).collect();
+        titles.sort();
+        titles
+    };
+
+    assert!(tracker.filter.status.is_all());
+    assert_eq!(titles(&tracker), ["Dos", "Tres", "Uno"]);
+
+    // Ocultar las terminadas deja pendientes y en progreso
+    let _ = tracker.update(Message::ToggleQueryStatus(Status::Done));
+    assert_eq!(titles(&tracker), ["Dos", "Uno"]);
+    assert!(!tracker.filter.status.is_all());
+
+    let _ = tracker.update(Message::ToggleQueryStatus(Status::ToDo));
+    assert_eq!(titles(&tracker), ["Dos"]);
+
+    // Quitar el último estado activo vuelve a mostrar todos en lugar de una lista vacía
+    let _ = tracker.update(Message::ToggleQueryStatus(Status::InProgress));
+    assert!(tracker.filter.status.is_all());
+    assert_eq!(titles(&tracker), ["Dos", "Tres", "Uno"]);
+
+    let _ = tracker.update(Message::ToggleQueryStatus(Status::ToDo));
+    let _ = tracker.update(Message::ToggleQueryStatus(Status::InProgress));
+    assert_eq!(titles(&tracker), ["Tres"]);
+
+    let _ = tracker.update(Message::ShowAllStatuses);
+    assert_eq!(titles(&tracker).len(), 3);
+}
+
+#[test]
+fn empty_saved_status_filter_shows_all() {
+    let preferences: Preferences = serde_json::from_str(r#"{"filter_status": {"to_do": false, "in_progress": false, "done": false}}"#).unwrap();
+    assert!(preferences.filter_status.is_all());
+
+    let preferences: Preferences = serde_json::from_str(r#"{"filter_status": {"to_do": false, "in_progress": true, "done": false}}"#).unwrap();
+    assert_eq!(preferences.filter_status, StatusFilter::only(Status::InProgress));
+}
+
+#[test]
+fn task_is_edited_after_modification() {
+    let mut task = Task::new("Informe".to_string(), "Trabajo".to_string());
+    assert!(!task.is_edited());
+
+    std::thread::sleep(Duration::from_millis(2));
+    task.set_title("Informe final".to_string());
+    task.modified();
+    assert!(task.is_edited());
+}
+
+#[test]
+fn editing_todo_task_can_propose_in_progress() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Informe".to_string(), "Trabajo".to_string());
+    let id = tracker.get_tasks()[0].id();
+    let view = |tracker: &TaskTracker| tracker.tasks.iter().find(|tv| tv.get_task().id() == id).unwrap().fields().status();
+
+    // Desactivado se mantiene el estado actual
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::ToggleState));
+    assert_eq!(view(&tracker), Status::ToDo);
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::CancelEdit));
+
+    tracker.preferences.auto_in_progress = true;
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::ToggleState));
+    assert_eq!(view(&tracker), Status::InProgress);
+    // La tarea guardada no cambia hasta aceptar
+    assert_eq!(tracker.get_task(id).unwrap().status, Status::ToDo);
+}
+
+#[test]
+fn due_today_and_overdue_bucket_by_due_date() {
+    let mut tracker = temp_tracker();
+    let now = Local::now().naive_local();
+    let today_noon = now.date().and_hms_opt(12, 0, 0).unwrap();
+    let due_dates = [
+        ("Ayer", now - TimeDelta::days(1)),
+        ("Hoy", today_noon),
+        ("Mañana", now + TimeDelta::days(1)),
+        ("Ayer terminada", now - TimeDelta::days(1))
+    ];
+    for (title, due_date) in due_dates {
+        tracker.add_task(title.to_string(), "Tarea".to_string());
+        let task = tracker.get_tasks_iter_mut().last().unwrap();
+        task.set_due_date(Some(due_date));
+        if title == "Ayer terminada" {
+            task.set_status(Status::Done);
+        }
+    }
+
+    let titles = |tasks: Vec<&Task>| tasks.iter().map(|task| task.title.clone()).collect::<Vec<_>>();
+    assert_eq!(titles(tracker.due_today()), ["Hoy"]);
+
+    // "Hoy" también está vencida si ya pasó el mediodía
+    let overdue = titles(tracker.overdue());
+    assert!(overdue.contains(&"Ayer".to_string()));
+    assert!(!overdue.contains(&"Mañana".to_string()));
+    assert!(!overdue.contains(&"Ayer terminada".to_string()));
+    assert_eq!(overdue.contains(&"Hoy".to_string()), today_noon < now);
+}
+
+#[test]
+fn notifications_are_not_repeated() {
+    let mut tracker = temp_tracker();
+    let now = Local::now().naive_local();
+    for (title, due_date) in [("Vencida", now - TimeDelta::days(2)), ("Lejana", now + TimeDelta::days(3))] {
+        tracker.add_task(title.to_string(), "Tarea".to_string());
+        tracker.get_tasks_iter_mut().last().unwrap().set_due_date(Some(due_date));
+    }
+
+    let notifications = tracker.take_notifications();
+    assert_eq!(notifications.len(), 1);
+    assert!(notifications[0].contains("Vencida"));
+    assert!(tracker.take_notifications().is_empty());
+
+    // Una tarea nueva que vence se avisa sin repetir las anteriores
+    tracker.add_task("Otra".to_string(), "Tarea".to_string());
+    tracker.get_tasks_iter_mut().last().unwrap().set_due_date(Some(now - TimeDelta::hours(1)));
+    let notifications = tracker.take_notifications();
+    assert_eq!(notifications.len(), 1);
+    assert!(notifications[0].contains("Otra"));
+
+    // La configuración por defecto revisa cada cinco minutos
+    assert_eq!(serde_json::from_str::<Preferences>("{}").unwrap().notify_interval_minutes, 5);
+    assert_eq!(Preferences::default().notify_interval_minutes, 5);
+}
+
+#[test]
+fn titles_and_descriptions_are_normalized() {
+    let mut task = Task::new("  Informe  \n", "\n\n  Primera línea   \n\n\n\nSegunda  \n\n");
+    assert_eq!(task.title, "Informe");
+    assert_eq!(task.description, "Primera línea\n\nSegunda");
+
+    task.modify(Some("\tRevisar ".to_string()), Some("Uno\n \n \nDos\nTres   ".to_string()), None);
+    assert_eq!(task.title, "Revisar");
+    assert_eq!(task.description, "Uno\n\nDos\nTres");
+
+    // Una sola línea en blanco se conserva
+    task.set_description("Párrafo\n\nOtro".to_string());
+    assert_eq!(task.description, "Párrafo\n\nOtro");
+}
+
+#[test]
+fn view_mode_is_persisted_without_losing_edits() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+    let mut tracker = TaskTracker::with_path(path.clone());
+    tracker.add_task("Informe".to_string(), "Trabajo".to_string());
+    let id = tracker.get_tasks()[0].id();
+    assert_eq!(tracker.preferences.view_mode, ViewMode::Normal);
+
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::ToggleState));
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::SetTitle("Informe final".to_string())));
+    let _ = tracker.update(Message::SetViewMode(ViewMode::Compact));
+
+    let task_view = tracker.tasks.iter().find(|tv| tv.get_task().id() == id).unwrap();
+    assert!(task_view.is_editing());
+    assert_eq!(task_view.fields().title(), "Informe final");
+
+    let tracker = TaskTracker::with_path(path);
+    assert_eq!(tracker.preferences.view_mode, ViewMode::Compact);
+}
+
+#[test]
+fn visible_window_covers_viewport_with_overscan() {
+    assert_eq!(visible_window(0, 0.0, 800.0, 100.0), 0..0);
+    assert_eq!(visible_window(5, 0.0, 800.0, 100.0), 0..5);
+    assert_eq!(visible_window(1000, 0.0, 800.0, 100.0), 0..19);
+    assert_eq!(visible_window(1000, 5000.0, 800.0, 100.0), 40..69);
+    // Al final de la lista no se pasa del total
+    assert_eq!(visible_window(1000, 99_950.0, 800.0, 100.0), 989..1000);
+}
+
+#[test]
+fn long_lists_keep_complete_results() {
+    let mut tracker = temp_tracker();
+    for index in 0..1000 {
+        tracker.add_task(format!("Tarea {}", index), "Carga".to_string());
+    }
+    assert!(tracker.visible_count() >= VIRTUALIZE_FROM);
+
+    // El filtro ve todas las tareas aunque solo se construyan algunas tarjetas
+    tracker.filter.text = "Tarea 99".to_string();
+    assert_eq!(tracker.visible_count(), 11);
+    tracker.filter.text.clear();
+    assert_eq!(tracker.visible_tasks().len(), 1000);
+
+    let _ = tracker.update(Message::SetViewMode(ViewMode::Compact));
+    assert!(visible_window(tracker.visible_count(), tracker.scroll_offset, tracker.viewport_height, 45.0).len() < 50);
+}
+
+#[test]
+fn create_dialog_discards_draft_on_escape() {
+    let mut tracker = temp_tracker();
+    tracker.preferences.create_in_modal = true;
+
+    let _ = tracker.update(Message::OpenCreate);
+    let _ = tracker.update(Message::SetTitle("Borrador".to_string()));
+    assert!(tracker.creating);
+
+    let _ = tracker.update(Message::Escape);
+    assert!(!tracker.creating);
+    assert!(tracker.title.is_empty());
+    assert!(tracker.get_tasks().is_empty());
+
+    // Crear desde el diálogo lo cierra
+    let _ = tracker.update(Message::OpenCreate);
+    let _ = tracker.update(Message::SetTitle("Informe".to_string()));
+    tracker.description = text_editor::Content::with_text("Trabajo");
+    let _ = tracker.update(Message::CreateFromForm);
+    assert!(!tracker.creating);
+    assert_eq!(tracker.get_tasks().len(), 1);
+
+    // El atajo global no crea nada con el diálogo cerrado
+    tracker.title = "Oculta".to_string();
+    tracker.description = text_editor::Content::with_text("Sin diálogo");
+    let _ = tracker.update(Message::CreateFromForm);
+    assert_eq!(tracker.get_tasks().len(), 1);
+}
+
+#[test]
+fn list_is_grouped_by_status_without_status_filter() {
+    let mut tracker = temp_tracker();
+    for (title, status) in [("Informe", Status::Done), ("Informe dos", Status::ToDo), ("Compras", Status::ToDo), ("Informe tres", Status::InProgress)] {
+        tracker.add_task(title.to_string(), "Tarea".to_string());
+        let id = tracker.get_tasks().last().unwrap().id();
+        tracker.update_task(id, None, None, Some(status));
+    }
+    let describe = |tracker: &TaskTracker| tracker.list_rows().iter().map(|row| match row {
+        ListRow::Section(status, count) => format!("{}:{}", status, count),
+        ListRow::Task(task) => task.get_task().title.clone()
+    }).collect::<Vec<_>>();
+
+    assert_eq!(describe(&tracker), ["Pendiente:2", "Informe dos", "Compras", "En progreso:1", "Informe tres", "Terminada:1", "Informe"]);
+
+    // La búsqueda filtra dentro de las secciones y oculta las vacías
+    tracker.filter.text = "informe".to_string();
+    let _ = tracker.update(Message::ToggleSection(Status::ToDo));
+    assert_eq!(describe(&tracker), ["Pendiente:1", "En progreso:1", "Informe tres", "Terminada:1", "Informe"]);
+
+    // Con un filtro por estado la lista es plana
+    let _ = tracker.update(Message::ToggleQueryStatus(Status::Done));
+    assert_eq!(describe(&tracker), ["Informe dos", "Informe tres"]);
+}
+
+#[test]
+fn accepted_edit_can_be_undone() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Informe".to_string(), "Trabajo".to_string());
+    let id = tracker.get_tasks()[0].id();
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::ToggleState));
+    let before = tracker.get_task(id).unwrap().clone();
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::Modify {
+        title: Some("Informe final".to_string()),
+        description: Some("Entregado".to_string()),
+        status: Some(Status::Done),
+        due_date: None, priority: None, tags: None, subtasks: None, color: None, depends_on: None, estimate_minutes: Some(Some(90))
+    }));
+    assert_eq!(tracker.get_task(id).unwrap().title, "Informe final");
+    assert!(tracker.can_undo_edit());
+
+    let _ = tracker.update(Message::UndoEdit(id));
+    assert_eq!(tracker.get_task(id).unwrap(), &before);
+    assert_eq!(tracker.tasks[0].fields().title(), "Informe");
+    assert!(tracker.last_edit.is_none());
+}
+
+#[test]
+fn unchanged_or_rejected_edits_are_not_recorded() {
+    let mut tracker = temp_tracker();
+    tracker.history = HistoryLog::Memory(Vec::new());
+    tracker.preferences.history_log = true;
+    tracker.preferences.strict_transitions = true;
+    let id = tracker.add_task("Informe".to_string(), "Trabajo".to_string());
+    let modify = |status| task::Message::Modify {
+        title: None, description: None, status, due_date: None, priority: None,
+        tags: None, subtasks: None, color: None, depends_on: None, estimate_minutes: None
+    };
+
+    // Sin cambios
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::ToggleState));
+    let _ = tracker.update(Message::TaskMessage(id, modify(None)));
+    // Rechazada por el modo estricto: de pendiente no se puede pasar a terminada
+    let _ = tracker.update(Message::TaskMessage(id, modify(Some(Status::Done))));
+
+    assert!(tracker.last_edit.is_none());
+    let HistoryLog::Memory(entries) = &tracker.history else {unreachable!()};
+    assert!(entries.iter().all(|entry| entry.operation != Operation::Edit));
+}
+
+#[test]
+fn operations_are_logged_when_enabled() {
+    let mut tracker = temp_tracker();
+    tracker.history = HistoryLog::Memory(Vec::new());
+    let entries = |tracker: &TaskTracker| -> Vec<(Operation, String)> {
+        match &tracker.history {
+            HistoryLog::Memory(entries) => entries.iter().map(|entry| (entry.operation, entry.title.clone())).collect(),
+            HistoryLog::File(_) => unreachable!()
+        }
+    };
+
+    // Desactivado por defecto
+    tracker.add_task("Borrador".to_string(), "No se registra".to_string());
+    assert!(entries(&tracker).is_empty());
+
+    let _ = tracker.update(Message::SetHistoryLog(true));
+    let id = tracker.add_task("Informe".to_string(), "Trabajo".to_string());
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::ToggleState));
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::Modify {
+        title: Some("Informe final".to_string()),
+        description: None, status: None, due_date: None, priority: None, tags: None, subtasks: None, color: None, depends_on: None, estimate_minutes: None
+    }));
+    // Aceptar sin cambios no agrega una entrada
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::ToggleState));
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::Modify {
+        title: None, description: None, status: None, due_date: None, priority: None, tags: None, subtasks: None, color: None, depends_on: None, estimate_minutes: None
+    }));
+    tracker.remove_task(id);
+
+    assert_eq!(entries(&tracker), vec![
+        (Operation::Create, "Informe".to_string()),
+        (Operation::Edit, "Informe final".to_string()),
+        (Operation::Delete, "Informe final".to_string()),
+    ]);
+    match &tracker.history {
+        HistoryLog::Memory(entries) => assert!(entries.iter().all(|entry| entry.id == id)),
+        HistoryLog::File(_) => unreachable!()
+    }
+}
+
+#[test]
+fn history_file_gets_one_json_line_per_entry() {
+    let dir = temp_dir();
+    let path = dir.join("history.log");
+    let mut history = HistoryLog::File(path.clone());
+    let task = Task::new("Informe", "Trabajo");
+
+    history.append(HistoryEntry::new(Operation::Create, &task)).unwrap();
+    history.append(HistoryEntry::new(Operation::Delete, &task)).unwrap();
+
+    let lines: Vec<HistoryEntry> = fs::read_to_string(&path).unwrap().lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1].operation, Operation::Delete);
+    assert_eq!(lines[1].id, task.id());
+
+    // Un destino que no se puede abrir falla sin entrar en pánico
+    let mut broken = HistoryLog::File(dir.join("no-existe").join("history.log"));
+    assert!(broken.append(HistoryEntry::new(Operation::Create, &task)).is_err());
+}
+
+#[test]
+fn status_orders_by_workflow() {
+    assert!(Status::ToDo < Status::InProgress);
+    assert!(Status::InProgress < Status::Done);
+
+    let mut statuses = Status::ALL.to_vec();
+    statuses.sort();
+    assert_eq!(statuses, vec![Status::ToDo, Status::InProgress, Status::Done]);
+    assert_eq!(Status::ALL.iter().max(), Some(&Status::Done));
+
+    // El orden no cambia la serialización
+    assert_eq!(serde_json::to_string(&Status::InProgress).unwrap(), "\"in-progress\"");
+    assert_eq!(serde_json::from_str::<Status>("\"to-do\"").unwrap(), Status::ToDo);
+}
+
+#[test]
+fn sort_by_status_follows_workflow() {
+    let mut tracker = temp_tracker();
+    for (title, status) in [("Terminada", Status::Done), ("Pendiente 1", Status::ToDo), ("En curso", Status::InProgress), ("Pendiente 2", Status::ToDo)] {
+        let id = tracker.add_task(title.to_string(), "Descripción".to_string());
+        tracker.get_task_mut(id).unwrap().set_status(status);
+    }
+
+    tracker.sort = SortBy::StatusAsc;
+    let titles: Vec<String> = tracker.visible_tasks().iter().map(|tv| tv.get_task().title.clone()).collect();
+    // Los empates conservan el orden de inserción
+    assert_eq!(titles, vec!["Pendiente 1", "Pendiente 2", "En curso", "Terminada"]);
+}
+
+#[test]
+fn strict_transitions_reject_skipping_steps() {
+    let mut task = Task::new("Informe", "Trabajo");
+
+    // Sin el modo estricto cualquier cambio está permitido
+    assert_eq!(Status::ToDo.check_transition(Status::Done, false), Ok(()));
+
+    // Saltar de pendiente a terminada no está permitido y no cambia nada
+    let error = task.try_set_status(Status::Done, true).unwrap_err();
+    assert_eq!(error, TransitionError { from: Status::ToDo, to: Status::Done });
+    assert!(error.to_string().contains("\"En progreso\""), "{}", error);
+    assert_eq!(task.status, Status::ToDo);
+    assert!(task.try_modify(Some("Otro".to_string()), None, Some(Status::Done), true).is_err());
+    assert_eq!(task.title, "Informe");
+
+    // Avanzar un paso a la vez sí
+    assert_eq!(task.try_set_status(Status::InProgress, true), Ok(()));
+    assert_eq!(task.try_modify(None, None, Some(Status::Done), true), Ok(()));
+    assert_eq!(task.status, Status::Done);
+    assert!(task.completed_at().is_some());
+    assert!(task.try_set_status(Status::ToDo, true).is_err());
+}
+
+#[test]
+fn strict_transitions_setting_guards_edits() {
+    let mut tracker = temp_tracker();
+    let id = tracker.add_task("Informe".to_string(), "Trabajo".to_string());
+    let other = tracker.add_task("Compras".to_string(), "Casa".to_string());
+    let _ = tracker.update(Message::SetStrictTransitions(true));
+
+    // La edición se rechaza completa y queda abierta con el motivo
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::ToggleState));
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::Modify {
+        title: Some("Informe final".to_string()), description: None, status: Some(Status::Done),
+        due_date: None, priority: None, tags: None, subtasks: None, color: None, depends_on: None, estimate_minutes: None
+    }));
+    assert_eq!(tracker.get_task(id).unwrap().status, Status::ToDo);
+    assert_eq!(tracker.get_task(id).unwrap().title, "Informe");
+    assert!(tracker.tasks[0].is_editing());
+
+    // Un cambio permitido se acepta
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::Modify {
+        title: None, description: None, status: Some(Status::InProgress),
+        due_date: None, priority: None, tags: None, subtasks: None, color: None, depends_on: None, estimate_minutes: None
+    }));
+    assert_eq!(tracker.get_task(id).unwrap().status, Status::InProgress);
+
+    // Los cambios en bloque omiten las tareas que saltarían un paso
+    assert_eq!(tracker.bulk_set_status(Status::Done), 1);
+    assert_eq!(tracker.get_task(other).unwrap().status, Status::ToDo);
+
+    tracker.update_task(other, None, None, Some(Status::Done));
+    assert_eq!(tracker.get_task(other).unwrap().status, Status::ToDo);
+    assert!(tracker.warning.is_some());
+
+    // Sin el modo estricto vuelve a estar permitido
+    let _ = tracker.update(Message::SetStrictTransitions(false));
+    tracker.update_task(other, None, None, Some(Status::Done));
+    assert_eq!(tracker.get_task(other).unwrap().status, Status::Done);
+}
+
+#[test]
+fn stats_over_fixed_dataset() {
+    let mut tracker = temp_tracker();
+
+    // Sin tareas todo es cero, sin dividir por cero
+    let now = local_to_utc(NaiveDate::from_ymd_opt(2024, 5, 15).unwrap().and_hms_opt(12, 0, 0).unwrap());
+    assert_eq!(tracker.stats_at(now), Stats::default());
+
+    // Miércoles 15 de mayo; la semana empieza el lunes 13
+    let at = |day, hour| local_to_utc(NaiveDate::from_ymd_opt(2024, 5, day).unwrap().and_hms_opt(hour, 0, 0).unwrap());
+    let mut restore = |title: &str, created_at, status| {
+        let mut task = Task::restore(uuid::Uuid::new_v4(), title.to_string(), String::new(), Status::ToDo, created_at, created_at);
+        task.set_status(status);
+        tracker.tasks.push(TaskView::from(task));
+    };
+    restore("Domingo", at(12, 12), Status::ToDo);
+    restore("Lunes", at(13, 0), Status::InProgress);
+    restore("Hoy", at(15, 6), Status::Done);
+    restore("Vieja", at(1, 12), Status::Done);
+    restore("Archivada", at(14, 12), Status::ToDo);
+    tracker.tasks[4].get_task_mut().archived = true;
+
+    let stats = tracker.stats_at(now);
+    assert_eq!((stats.to_do, stats.in_progress, stats.done), (1, 1, 2));
+    assert_eq!(stats.created_this_week, 2);
+    // Las abiertas tienen 3 días y 2 días 12 horas
+    assert_eq!(stats.average_open_age, Some(TimeDelta::hours(66)));
+    assert_eq!(stats.completion_rate, 0.5);
+
+    assert_eq!(format_age(TimeDelta::hours(66)), "2 días");
+    assert_eq!(format_age(TimeDelta::hours(30)), "1 día");
+    assert_eq!(format_age(TimeDelta::minutes(90)), "1h 30m");
+}
+
+#[test]
+fn tasks_by_date_match_the_whole_day() {
+    let mut tracker = temp_tracker();
+    let day = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+    let created = [("Mañana", day.and_hms_opt(9, 0, 0)), ("Tarde", day.and_hms_opt(18, 30, 0)), ("Otro día", day.succ_opt().unwrap().and_hms_opt(9, 0, 0))];
+    for (title, local) in created {
+        let created_at = local_to_utc(local.unwrap());
+        tracker.tasks.push(TaskView::from(Task::restore(uuid::Uuid::new_v4(), title.to_string(), String::new(), Status::ToDo, created_at, created_at)));
+    }
+
+    let titles: Vec<&str> = tracker.get_tasks_by_date(day.and_hms_opt(12, 0, 0).unwrap()).iter().map(|task| task.title.as_str()).collect();
+    assert_eq!(titles, ["Mañana", "Tarde"]);
+}
+
+#[test]
+fn today_filter_uses_local_day_boundaries() {
+    let day = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+    let created = |hour, minute, second| {
+        let created_at = local_to_utc(day.and_hms_opt(hour, minute, second).unwrap());
+        Task::restore(uuid::Uuid::new_v4(), "Tarea".to_string(), String::new(), Status::ToDo, created_at, created_at)
+    };
+
+    assert!(created(0, 0, 0).falls_on(day));
+    assert!(created(23, 59, 59).falls_on(day));
+    assert!(!created(23, 59, 59).falls_on(day.pred_opt().unwrap()));
+    assert!(!created(0, 0, 0).falls_on(day.succ_opt().unwrap()));
+
+    // También cuenta si vence ese día, aunque se haya creado antes
+    let mut task = created(10, 0, 0);
+    task.set_due_date(Some(day.succ_opt().unwrap().and_hms_opt(0, 0, 0).unwrap()));
+    assert!(task.falls_on(day.succ_opt().unwrap()));
+    assert!(!task.falls_on(day.succ_opt().unwrap().succ_opt().unwrap()));
+}
+
+#[test]
+fn today_filter_combines_with_search() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Informe".to_string(), "Trabajo".to_string());
+    tracker.add_task("Compras".to_string(), "Casa".to_string());
+    let old = local_to_utc(NaiveDate::from_ymd_opt(2020, 1, 1).unwrap().and_hms_opt(9, 0, 0).unwrap());
+    tracker.tasks.push(TaskView::from(Task::restore(uuid::Uuid::new_v4(), "Informe viejo".to_string(), String::new(), Status::ToDo, old, old)));
+
+    let _ = tracker.update(Message::ToggleToday);
+    assert_eq!(tracker.visible_count(), 2);
+    tracker.filter.text = "informe".to_string();
+    assert_eq!(tracker.visible_count(), 1);
+
+    let _ = tracker.update(Message::ToggleToday);
+    assert!(tracker.filter.date.is_none());
+    assert_eq!(tracker.visible_count(), 2);
+}
+
+#[test]
+fn created_range_combines_with_other_filters() {
+    let mut tracker = temp_tracker();
+    let day = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+    let mut restore = |title: &str, created: NaiveDate, status: Status| {
+        let created_at = local_to_utc(created.and_hms_opt(23, 30, 0).unwrap());
+        tracker.tasks.push(TaskView::from(Task::restore(uuid::Uuid::new_v4(), title.to_string(), String::new(), status, created_at, created_at)));
+    };
+    restore("Informe mayo", day(1), Status::ToDo);
+    restore("Informe semanal", day(10), Status::Done);
+    restore("Compras", day(10), Status::ToDo);
+    restore("Informe final", day(20), Status::ToDo);
+
+    let titles = |tracker: &TaskTracker| -> Vec<String> {
+        tracker.visible_tasks().iter().map(|tv| tv.get_task().title.clone()).collect()
+    };
+
+    // Sin límites no se filtra nada
+    assert_eq!(titles(&tracker).len(), 4);
+
+    // Solo desde: el primer día es inclusive, incluso a última hora
+    let _ = tracker.update(Message::SetCreatedRange(RangeBound::From, Some(day(10))));
+    assert_eq!(titles(&tracker), vec!["Informe semanal", "Compras", "Informe final"]);
+
+    // Rango cerrado, el último día también es inclusive
+    let _ = tracker.update(Message::SetCreatedRange(RangeBound::To, Some(day(10))));
+    assert_eq!(titles(&tracker), vec!["Informe semanal", "Compras"]);
+
+    // Se combina con la búsqueda y el estado
+    tracker.filter.text = "informe".to_string();
+    assert_eq!(titles(&tracker), vec!["Informe semanal"]);
+    let _ = tracker.update(Message::ToggleQueryStatus(Status::Done));
+    assert!(titles(&tracker).is_empty());
+
+    // Solo hasta
+    let _ = tracker.update(Message::ShowAllStatuses);
+    let _ = tracker.update(Message::SetCreatedRange(RangeBound::From, None));
+    assert_eq!(titles(&tracker), vec!["Informe mayo", "Informe semanal"]);
+
+    let _ = tracker.update(Message::SetCreatedRange(RangeBound::To, None));
+    assert!(!tracker.filter.has_created_range());
+    assert_eq!(titles(&tracker).len(), 3);
+
+    // La consulta directa usa la misma lógica
+    let start = day(1).and_hms_opt(0, 0, 0).unwrap();
+    let end = day(10).and_hms_opt(23, 59, 59).unwrap();
+    assert_eq!(tracker.get_tasks_by_date_range(start, end).len(), 3);
+}
+
+#[test]
+fn task_dto_round_trips_with_same_format() {
+    let mut task = Task::new("Informe", "Trabajo");
+    task.set_priority(Priority::High);
+    task.set_tags(vec!["trabajo".to_string()]);
+    task.add_subtask("Borrador");
+    task.set_color(Some([255, 0, 0]));
+    task.depends_on = vec![uuid::Uuid::new_v4()];
+    task.set_status(Status::InProgress);
+    task.set_status(Status::Done);
+
+    let dto = TaskDto::from(&task);
+    assert_eq!(serde_json::to_value(&dto).unwrap(), serde_json::to_value(&task).unwrap());
+    assert_eq!(Task::try_from(dto.clone()).unwrap(), task);
+
+    let untitled = TaskDto { title: "  ".to_string(), ..dto.clone() };
+    assert_eq!(Task::try_from(untitled).unwrap_err(), DtoError::EmptyTitle);
+    let backwards = TaskDto { modified_at: dto.created_at - TimeDelta::seconds(1), ..dto };
+    assert_eq!(Task::try_from(backwards).unwrap_err(), DtoError::ModifiedBeforeCreated);
+}
+
+#[test]
+fn escape_in_search_clears_query() {
+    let mut tracker = temp_tracker();
+    tracker.filter.text = "informe".to_string();
+
+    // Escape con el foco en otro campo no toca la búsqueda
+    let _ = tracker.update(Message::EscapeFocused(Some(crate::title_input_id().into())));
+    assert_eq!(tracker.filter.text, "informe");
+
+    let _ = tracker.update(Message::EscapeFocused(Some(crate::search_input_id().into())));
+    assert!(tracker.filter.text.is_empty());
+}
+
+#[test]
+fn opening_a_task_records_last_viewed_at() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+    let mut tracker = TaskTracker::with_path(path.clone());
+    tracker.add_task("Informe".to_string(), "Trabajo".to_string());
+    tracker.add_task("Compras".to_string(), "Casa".to_string());
+    tracker.flush();
+    let id = tracker.get_tasks()[0].id();
+    assert!(tracker.get_task(id).unwrap().last_viewed_at.is_none());
+
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::ToggleState));
+    let viewed = tracker.get_task(id).unwrap().last_viewed_at;
+    assert!(viewed.is_some());
+    assert!(tracker.is_dirty());
+
+    // Se guarda y permite ordenar por las vistas más recientes
+    tracker.flush();
+    let mut tracker = TaskTracker::with_path(path);
+    assert_eq!(tracker.get_task(id).unwrap().last_viewed_at, viewed);
+    tracker.sort = SortBy::ViewedDesc;
+    assert_eq!(tracker.visible_tasks()[0].get_task().id(), id);
+}
+
+#[test]
+fn minutes_are_formatted_as_hours_and_minutes() {
+    assert_eq!(format_minutes(0), "0m");
+    assert_eq!(format_minutes(45), "45m");
+    assert_eq!(format_minutes(60), "1h");
+    assert_eq!(format_minutes(90), "1h 30m");
+    assert_eq!(format_minutes(605), "10h 5m");
+}
+
+#[test]
+fn estimate_is_edited_from_the_form() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Informe".to_string(), "Trabajo".to_string());
+    let id = tracker.get_tasks()[0].id();
+
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::ToggleState));
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::SetEstimate("9x".to_string())));
+    let _ = tracker.update(Message::TaskMessage(id, task::Message::SetEstimate("90".to_string())));
+    assert_eq!(tracker.tasks[0].fields().estimate(), Some(90));
+
+    // Los archivos sin el campo se leen sin estimado
+    let task: Task = serde_json::from_str(r#"{"id":"67e55044-10b1-426f-9247-bb680e5fe0c8","title":"Viejo","description":"","status":"to-do","created_at":"2024-01-01T00:00:00Z","modified_at":"2024-01-01T00:00:00Z"}"#).unwrap();
+    assert_eq!(task.estimate_minutes, None);
+}
+
+#[test]
+fn status_labels_can_be_reordered_and_renamed() {
+    let defaults = StatusLabels::default();
+    assert_eq!(defaults.order(), [Status::ToDo, Status::InProgress, Status::Done]);
+    for status in defaults.order() {
+        assert_eq!(defaults.get(status).singular, status.to_string());
+    }
+
+    // Los repetidos se ignoran y los que faltan se agregan al final
+    let labels = StatusLabels {
+        order: vec![Status::Done, Status::Done, Status::ToDo],
+        done: StatusLabel { singular: "Lista".to_string(), plural: "Listas".to_string() },
+        ..StatusLabels::default()
+    };
+    assert_eq!(labels.order(), [Status::Done, Status::ToDo, Status::InProgress]);
+    assert_eq!(labels.get(Status::Done).plural, "Listas");
+
+    // Las secciones siguen el orden configurado
+    let mut tracker = temp_tracker();
+    tracker.preferences.status_labels = labels;
+    for status in [Status::ToDo, Status::Done] {
+        tracker.add_task("Tarea".to_string(), "Tarea".to_string());
+        let id = tracker.get_tasks().last().unwrap().id();
+        tracker.update_task(id, None, None, Some(status));
+    }
+    let sections: Vec<Status> = tracker.list_rows().iter().filter_map(|row| match row {
+        ListRow::Section(status, _) => Some(*status),
+        ListRow::Task(_) => None
+    }).collect();
+    assert_eq!(sections, [Status::Done, Status::ToDo]);
+
+    // Un archivo de preferencias parcial completa el resto con los valores por defecto
+    let preferences: Preferences = serde_json::from_str(r#"{"status_labels":{"order":["in-progress"]}}"#).unwrap();
+    assert_eq!(preferences.status_labels.order(), [Status::InProgress, Status::ToDo, Status::Done]);
+    assert_eq!(preferences.status_labels.to_do, StatusLabels::default().to_do);
+}
+
+#[test]
+fn clipboard_text_lists_task_details() {
+    let mut task = Task::new("Informe", "Revisar cifras\nEnviar al equipo");
+    let created = format_timestamp(task.created_at(), crate::utils::locale());
+    assert_eq!(
+        task_to_clipboard_text(&task),
+        format!("Informe\nEstado: Pendiente\nCreado: {}\n\nRevisar cifras\nEnviar al equipo\n", created)
+    );
+
+    // Sin descripción no queda la línea en blanco
+    task.set_description(String::new());
+    let due_date = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap().and_hms_opt(18, 0, 0).unwrap();
+    task.set_due_date(Some(due_date));
+    assert_eq!(
+        task_to_clipboard_text(&task),
+        format!("Informe\nEstado: Pendiente\nVence: {}\nCreado: {}\n", format_date_time(due_date, crate::utils::locale()), created)
+    );
+}
+
+#[test]
+fn accept_ignores_blank_title_and_description() {
+    let task = Task::new("Informe", "Trabajo");
+    let mut view = TaskView::from(&task);
+    let _ = view.update(task::Message::ToggleState);
+    let _ = view.update(task::Message::SetTitle("   ".to_string()));
+    let _ = view.update(task::Message::SetDescription(text_editor::Action::SelectAll));
+    let _ = view.update(task::Message::SetDescription(text_editor::Action::Edit(text_editor::Edit::Paste(std::sync::Arc::new(" \n  ".to_string())))));
+
+    let task::Message::Modify { title, description, status, .. } = view.accept_message() else {
+        panic!("Aceptar debe enviar Modify")
+    };
+    assert_eq!((title, description, status), (None, None, None));
+
+    // Los espacios alrededor de un título nuevo no cuentan como cambio si el texto es el mismo
+    let _ = view.update(task::Message::SetTitle("  Informe  ".to_string()));
+    let task::Message::Modify { title, .. } = view.accept_message() else {unreachable!()};
+    assert_eq!(title, None);
+
+    let _ = view.update(task::Message::SetTitle(" Informe final ".to_string()));
+    let task::Message::Modify { title, .. } = view.accept_message() else {unreachable!()};
+    assert_eq!(title.as_deref(), Some("Informe final"));
+}
+
+#[test]
+fn filter_tasks_combines_predicates() {
+    let mut tracker = temp_tracker();
+    for (title, status) in [("Informe semanal", Status::Done), ("Informe mensual", Status::ToDo), ("Compras", Status::Done)] {
+        tracker.add_task(title.to_string(), "Tarea".to_string());
+        let id = tracker.get_tasks().last().unwrap().id();
+        tracker.update_task(id, None, None, Some(status));
+    }
+
+    let titles: Vec<&str> = tracker
+        .filter_tasks(|task| task.status == Status::Done && task.title.contains("Informe"))
+        .map(|task| task.title.as_str())
+        .collect();
+    assert_eq!(titles, ["Informe semanal"]);
+    assert_eq!(tracker.filter_tasks(|_| true).count(), 3);
+}
+#[test]
+fn parse_quick_add_extracts_tokens() {
+    let today = NaiveDate::from_ymd_opt(2024, 5, 31).unwrap();
+    let end_of = |date: NaiveDate| date.and_hms_opt(23, 59, 0).unwrap();
+
+    let parsed = parse_quick_add_at("Comprar pan !high #casa due:mañana", today);
+    assert_eq!(parsed, ParsedTask {
+        title: "Comprar pan".to_string(),
+        priority: Some(Priority::High),
+        tags: vec!["casa".to_string()],
+        due_date: Some(end_of(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap())),
+    });
+
+    // Los tokens pueden ir en cualquier posición; las etiquetas repetidas se ignoran y gana la última prioridad
+    let parsed = parse_quick_add_at("#trabajo Enviar !BAJA informe #trabajo #urgente due:hoy !media", today);
+    assert_eq!(parsed.title, "Enviar informe");
+    assert_eq!(parsed.priority, Some(Priority::Medium));
+    assert_eq!(parsed.tags, ["trabajo", "urgente"]);
+    assert_eq!(parsed.due_date, Some(end_of(today)));
+
+    let expected = Some(end_of(NaiveDate::from_ymd_opt(2024, 7, 15).unwrap()));
+    assert_eq!(parse_quick_add_at("Pagar due:2024-07-15", today).due_date, expected);
+    assert_eq!(parse_quick_add_at("Pagar due:15/07/2024", today).due_date, expected);
+}
+
+#[test]
+fn parse_quick_add_keeps_unrecognized_tokens() {
+    let today = NaiveDate::from_ymd_opt(2024, 5, 31).unwrap();
+
+    let parsed = parse_quick_add_at("  Llamar   a  Ana  ", today);
+    assert_eq!(parsed, ParsedTask { title: "Llamar a Ana".to_string(), ..ParsedTask::default() });
+
+    let parsed = parse_quick_add_at("¡Hola! !urgente # due:ayer due:2024-13-01 C# #ok", today);
+    assert_eq!(parsed.title, "¡Hola! !urgente # due:ayer due:2024-13-01 C#");
+    assert_eq!(parsed.priority, None);
+    assert_eq!(parsed.tags, ["ok"]);
+    assert_eq!(parsed.due_date, None);
+
+    // Un título solo con atributos queda vacío
+    assert_eq!(parse_quick_add_at("!alta #casa", today).title, "");
+}
+
+#[test]
+fn add_quick_task_applies_parsed_attributes() {
+    let mut tracker = temp_tracker();
+
+    let id = tracker.add_quick_task("Comprar pan !alta #casa", "Integral".to_string());
+    let task = tracker.get_task(id).unwrap();
+    assert_eq!(task.title, "Comprar pan");
+    assert_eq!(task.description, "Integral");
+    assert_eq!(task.priority, Priority::High);
+    assert_eq!(task.tags, ["casa"]);
+    assert_eq!(task.due_date, None);
+
+    let id = tracker.add_quick_task("Sin atributos", String::new());
+    assert_eq!(tracker.get_task(id).unwrap().priority, Priority::default());
+}
+
+#[test]
+fn favorites_sort_first_within_current_order() {
+    let mut tracker = temp_tracker();
+    let first = tracker.add_task("Primera".to_string(), String::new());
+    tracker.add_task("Segunda".to_string(), String::new());
+    let third = tracker.add_task("Tercera".to_string(), String::new());
+
+    tracker.toggle_favorite(third);
+    let titles = |tracker: &TaskTracker| tracker.visible_tasks().iter().map(|tv| tv.get_task().title.clone()).collect::<Vec<_>>();
+    assert_eq!(titles(&tracker), ["Tercera", "Primera", "Segunda"]);
+
+    // Entre favoritas se respeta el orden elegido
+    tracker.toggle_favorite(first);
+    tracker.sort = SortBy::TitleAsc;
+    assert_eq!(titles(&tracker), ["Primera", "Tercera", "Segunda"]);
+
+    tracker.toggle_favorite(first);
+    tracker.toggle_favorite(third);
+    assert_eq!(titles(&tracker), ["Primera", "Segunda", "Tercera"]);
+}
+
+#[test]
+fn remove_where_deletes_matching_tasks() {
+    let mut tracker = temp_tracker();
+    for (title, status) in [("Informe", Status::Done), ("Compras", Status::ToDo), ("Correo", Status::Done), ("Llamada", Status::InProgress)] {
+        let id = tracker.add_task(title.to_string(), String::new());
+        tracker.update_task(id, None, None, Some(status));
+    }
+
+    assert_eq!(tracker.remove_where(|task| task.status == Status::Done), 2);
+    let titles: Vec<&str> = tracker.get_tasks().iter().map(|task| task.title.as_str()).collect();
+    assert_eq!(titles, ["Compras", "Llamada"]);
+    assert!(tracker.last_deleted.is_none());
+
+    // Las eliminaciones quedan en el archivo
+    tracker.flush();
+    assert_eq!(read_tasks(&tracker.path).unwrap().len(), 2);
+
+    // Sin coincidencias no cambia nada ni hay nada que escribir
+    assert_eq!(tracker.remove_where(|task| task.status == Status::Done), 0);
+    assert_eq!(tracker.get_tasks().len(), 2);
+    assert!(!tracker.is_dirty());
+}
+
+#[test]
+fn remove_filtered_uses_current_filter() {
+    let mut tracker = temp_tracker();
+    tracker.add_task("Informe semanal".to_string(), String::new());
+    tracker.add_task("Informe mensual".to_string(), String::new());
+    tracker.add_task("Compras".to_string(), String::new());
+    assert!(!tracker.filter.is_filtering());
+
+    tracker.filter.text = "informe".to_string();
+    assert!(tracker.filter.is_filtering());
+    let _ = tracker.update(Message::RequestRemoveFiltered);
+    assert!(tracker.pending_remove_filtered);
+    let _ = tracker.update(Message::RemoveFiltered);
+
+    assert!(!tracker.pending_remove_filtered);
+    let titles: Vec<&str> = tracker.get_tasks().iter().map(|task| task.title.as_str()).collect();
+    assert_eq!(titles, ["Compras"]);
+}
+
+#[test]
+fn format_date_time_as_uses_custom_formats() {
+    let date_time = NaiveDate::from_ymd_opt(2024, 5, 20).unwrap().and_hms_opt(15, 30, 5).unwrap();
+
+    assert_eq!(format_date_time_as(date_time, "%Y-%m-%d %H:%M", DEFAULT_LOCALE), "2024-05-20 15:30");
+    assert_eq!(format_date_time_as(date_time, "%d/%m/%y %I:%M %p", DEFAULT_LOCALE), "20/05/24 03:30 PM");
+    assert_eq!(format_date_time_as(date_time, "%A %d de %B", Locale::es_PE), "lunes 20 de mayo");
+    assert_eq!(format_date_time_as(date_time, DEFAULT_DATE_TIME_FORMAT, DEFAULT_LOCALE), format_date_time(date_time, DEFAULT_LOCALE));
+
+    // El formato por defecto se muestra igual en todos los idiomas, también sin reloj de 12 horas
+    assert_eq!(DEFAULT_DATE_TIME_FORMAT, format!("{} - {}", crate::utils::DEFAULT_DATE_FORMAT, crate::utils::DEFAULT_TIME_FORMAT));
+    assert_eq!(format_date_time_as(date_time, DEFAULT_DATE_TIME_FORMAT, Locale::es_ES), format_date_time(date_time, Locale::es_ES));
+    assert!(format_date_time(date_time, Locale::es_ES).ends_with("03:30:05 PM"));
+
+    // Los formatos vacíos o no válidos usan el predeterminado
+    for format in ["", "  ", "%Q", "%Y-%"] {
+        assert!(!is_valid_date_time_format(format), "{:?}", format);
+        assert_eq!(format_date_time_as(date_time, format, DEFAULT_LOCALE), format_date_time(date_time, DEFAULT_LOCALE));
+    }
+    assert!(is_valid_date_time_format("%Y-%m-%d %H:%M"));
+}
+
+#[test]
+fn preferences_default_date_time_format() {
+    assert_eq!(Preferences::default().date_time_format, DEFAULT_DATE_TIME_FORMAT);
+
+    let preferences: Preferences = serde_json::from_str("{}").unwrap();
+    assert_eq!(preferences.date_time_format, DEFAULT_DATE_TIME_FORMAT);
+
+    let preferences: Preferences = serde_json::from_str(r#"{"date_time_format": "%H:%M"}"#).unwrap();
+    assert_eq!(preferences.date_time_format, "%H:%M");
+}
+
+#[test]
+fn stale_tasks_around_threshold() {
+    let mut tracker = temp_tracker();
+    let now = chrono::Utc::now().naive_utc();
+    let threshold = Duration::from_secs(30 * 24 * 60 * 60);
+
+    for (title, age, status) in [
+        ("Reciente", TimeDelta::days(1), Status::ToDo),
+        ("Justo en el límite", TimeDelta::days(30), Status::ToDo),
+        ("Pasado el límite", TimeDelta::days(30) + TimeDelta::seconds(1), Status::InProgress),
+        ("Antigua", TimeDelta::days(90), Status::ToDo),
+        ("Antigua terminada", TimeDelta::days(90), Status::Done),
+    ] {
+        let id = tracker.add_task(title.to_string(), String::new());
+        let task = tracker.get_task_mut(id).unwrap();
+        task.set_status(status);
+        task.modified_at = now - age;
+    }
+
+    let titles: Vec<&str> = tracker.stale_tasks_at(now, threshold).iter().map(|task| task.title.as_str()).collect();
+    assert_eq!(titles, ["Pasado el límite", "Antigua"]);
+
+    // Con el umbral en 0 días no se marca ninguna
+    assert_eq!(tracker.stale_threshold(), Some(threshold));
+    tracker.preferences.stale_after_days = 0;
+    assert_eq!(tracker.stale_threshold(), None);
+    assert!(tracker.get_tasks().iter().all(|task| !tracker.is_stale(task)));
+}
+
+#[test]
+fn cli_parse_args() {
+    let args = |args: &[&str]| cli::parse_args(args.iter().map(|arg| arg.to_string()));
+
+    assert!(matches!(args(&[]), Ok(None)));
+    assert!(matches!(args(&["export", "-"]), Ok(Some(cli::Command::Export))));
+    assert!(matches!(args(&["import", "-"]), Ok(Some(cli::Command::Import))));
+
+    for invalid in [&["export"][..], &["import", "tareas.json"], &["export", "-", "-"], &["borrar", "-"]] {
+        let error = args(invalid).unwrap_err();
+        assert!(matches!(error, cli::CliError::Usage(_)), "{:?}", invalid);
+        assert_eq!(error.exit_code(), 2);
+    }
+}
+
+#[test]
+fn cli_export_and_import_through_readers() {
+    let dir = temp_dir();
+    let path = dir.join("tasks.json");
+    let first = Task::new("Primera", "Local");
+    write_tasks(&path, vec![&first]).unwrap();
+
+    let mut output: Vec<u8> = Vec::new();
+    cli::run(cli::Command::Export, &path, std::io::empty(), &mut output).unwrap();
+    let exported = String::from_utf8(output).unwrap();
+    assert_eq!(parse_tasks(&exported).unwrap(), std::slice::from_ref(&first));
+
+    // Se une con lo guardado: las tareas nuevas se agregan y las conocidas se conservan
+    let second = Task::new("Segunda", "Importada");
+    let input = serde_json::to_string(&[&first, &second]).unwrap();
+    cli::run(cli::Command::Import, &path, input.as_bytes(), std::io::sink()).unwrap();
+    assert_eq!(read_tasks(&path).unwrap(), [first, second]);
+
+    // Una entrada inválida es un error y no cambia el archivo
+    let before = fs::read_to_string(&path).unwrap();
+    let error = cli::run(cli::Command::Import, &path, "no es json".as_bytes(), std::io::sink()).unwrap_err();
+    assert!(matches!(error, cli::CliError::Parse(_)));
+    assert_eq!(error.exit_code(), 1);
+    assert_eq!(fs::read_to_string(&path).unwrap(), before);
+
+    // Un fallo al escribir la exportación es de entrada/salida, no de JSON
+    let mut full = [0u8; 4];
+    let error = cli::export_to(&read_tasks(&path).unwrap(), &mut full[..]).unwrap_err();
+    assert!(matches!(error, cli::CliError::Io(_)));
+
+    // Exportar no crea un archivo que no existe ni mueve uno inválido
+    let missing = dir.join("nuevo").join("tasks.json");
+    let mut output: Vec<u8> = Vec::new();
+    cli::run(cli::Command::Export, &missing, std::io::empty(), &mut output).unwrap();
+    assert!(parse_tasks(&String::from_utf8(output).unwrap()).unwrap().is_empty());
+    assert!(!missing.exists());
+
+    fs::write(&path, "no es json").unwrap();
+    let error = cli::run(cli::Command::Export, &path, std::io::empty(), std::io::sink()).unwrap_err();
+    assert!(matches!(error, cli::CliError::Invalid(_)));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "no es json");
+    assert!(!dir.join("tasks.json.bak").exists());
+}
+
+#[test]
+fn duplicated_and_imported_tasks_are_logged() {
+    let mut tracker = temp_tracker();
+    tracker.history = HistoryLog::Memory(Vec::new());
+    tracker.preferences.history_log = true;
+
+    let id = tracker.add_task("Informe".to_string(), "Trabajo".to_string());
+    tracker.duplicate_task(id);
+    let imported = Task::new("Importada", "Externa");
+    let incoming = vec![imported.clone(), tracker.get_task(id).unwrap().clone()];
+    assert_eq!(tracker.merge_tasks(incoming), 1);
+
+    let HistoryLog::Memory(entries) = &tracker.history else {unreachable!()};
+    let created: Vec<&str> = entries.iter().filter(|entry| entry.operation == Operation::Create).map(|entry| entry.title.as_str()).collect();
+    assert_eq!(created, ["Informe", "Informe (copia)", "Importada"]);
+}
+
+#[test]
+fn text_preferences_are_saved_on_flush() {
+    let mut tracker = temp_tracker();
+    let settings = tracker.dir.join("settings.json");
+
+    let _ = tracker.update(Message::SetDateTimeFormat("%Y-%m-%d".to_string()));
+    let _ = tracker.update(Message::SetStaleAfterDays("7".to_string()));
+    assert!(tracker.is_dirty());
+    assert!(!settings.exists());
+
+    tracker.flush();
+    assert!(!tracker.is_dirty());
+    let saved = load_settings(&settings).preferences;
+    assert_eq!(saved.date_time_format, "%Y-%m-%d");
+    assert_eq!(saved.stale_after_days, 7);
+}
@@ -1,33 +1,989 @@
-use std::fs;
+use std::{env, fmt::{self, Display, Write}, fs, hash::{DefaultHasher, Hash, Hasher}, io, path::{Path, PathBuf}, sync::OnceLock, time::{Duration, SystemTime}};
 
-use chrono::{Locale, NaiveDateTime};
+use chrono::{Local, Locale, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::task::Task;
+use crate::task::{Priority, Status, Task};
 
-/// Lee el archivo "tasks.json" y obtiene las tareas alamacenadas en él.
+/// Nombre del archivo de tareas usado históricamente en el directorio de trabajo.
+pub const TASKS_FILE: &str = "tasks.json";
+
+/// Nombre del archivo generado al exportar las tareas a Markdown.
+pub const MARKDOWN_FILE: &str = "tasks.md";
+
+/// Nombre del archivo usado para exportar e importar tareas en CSV.
+pub const CSV_FILE: &str = "tasks.csv";
+
+/// Nombre del archivo usado para exportar e importar tareas en JSON Lines.
+pub const JSONL_FILE: &str = "tasks.jsonl";
+
+/// Variable de entorno que permite indicar la ruta del archivo de tareas.
+pub const TASKS_FILE_ENV: &str = "TASK_TRACKER_FILE";
+
+/// Versión actual del formato del archivo de tareas, ver [migrate]
+pub const TASKS_VERSION: u64 = 1;
+
+/// Variable de entorno que permite elegir el idioma de las fechas, p. ej. "es_ES" o "en_US".
+pub const LOCALE_ENV: &str = "TASK_TRACKER_LOCALE";
+
+/// Idioma de las fechas si no se indica otro en [LOCALE_ENV]
+pub const DEFAULT_LOCALE: Locale = Locale::es_PE;
+
+/// Errores que pueden ocurrir al leer el archivo de tareas.
+/// * _`Io:`_ El archivo existe pero no se pudo leer
+/// * _`Parse:`_ El contenido del archivo no es un JSON válido de tareas
+/// * _`Version:`_ El archivo es de una versión del formato más nueva que [TASKS_VERSION]
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    Version(u64),
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::Io(error) => write!(f, "No se pudo leer el archivo de tareas: {}", error),
+            ReadError::Parse(error) => write!(f, "El archivo de tareas está dañado, se guardó una copia con extensión \".bak\": {}", error),
+            ReadError::Version(version) => write!(f,
+                "El archivo de tareas es de la versión {}, más nueva que la soportada ({}). No se guardarán cambios para no sobrescribirlo",
+                version, TASKS_VERSION
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(error: io::Error) -> Self {
+        ReadError::Io(error)
+    }
+}
+
+/// Obtiene la ruta del archivo de tareas en este orden:
+/// 1. La variable de entorno `TASK_TRACKER_FILE`
+/// 2. "tasks.json" en el directorio actual, si ya existe (usuarios anteriores)
+/// 3. "task_tracker/tasks.json" dentro del directorio de configuración de la plataforma
+pub fn default_tasks_path() -> PathBuf {
+    if let Some(path) = env::var_os(TASKS_FILE_ENV).filter(|path| !path.is_empty()) {
+        return PathBuf::from(path);
+    }
+
+    let legacy = PathBuf::from(TASKS_FILE);
+    if legacy.exists() {
+        return legacy;
+    }
+
+    match dirs::config_dir() {
+        Some(dir) => dir.join("task_tracker").join(TASKS_FILE),
+        None => legacy
+    }
+}
+
+/// Agrega `extension` al final de la ruta, p. ej. "tasks.json" -> "tasks.json.bak".
+fn with_suffix(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+/// Lee el archivo de tareas en `path` y obtiene las tareas alamacenadas en él.
 /// Si el archivo no existe, lo crea y retorna un vector vacío.
-pub fn read_tasks() -> Vec<Task> {
-    let tasks = fs::read_to_string("tasks.json")
-        .unwrap_or_else(|_| {
-            let empty: Vec<Task> = Vec::new();
-            fs::write("tasks.json", serde_json::to_string(&empty).unwrap()).unwrap();
-            serde_json::to_string(&empty).unwrap()
+/// Si el contenido no es válido, lo renombra a "`path`.bak" para conservarlo y retorna un error,
+/// salvo que sea de una versión más nueva del formato: ese archivo se deja intacto, ver [ReadError::Version].
+pub fn read_tasks(path: &Path) -> Result<Vec<Task>, ReadError> {
+    let tasks = match fs::read_to_string(path) {
+        Ok(tasks) => tasks,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            let empty = tasks_to_json(Vec::new()).map_err(ReadError::Parse)?;
+            fs::write(path, &empty)?;
+            empty
+        }
+        Err(error) => return Err(ReadError::Io(error)),
+    };
+
+    parse_tasks(&tasks).map_err(|error| {
+        if let Some(version) = newer_version(&tasks) {
+            return ReadError::Version(version);
+        }
+        // Se conserva el archivo dañado para no perder las tareas al volver a escribir
+        let _ = fs::rename(path, backup_path(path));
+        ReadError::Parse(error)
+    })
+}
+
+/// Ruta de la copia de un archivo dañado: "`path`.bak", o con la fecha y hora si esa copia ya existe
+fn backup_path(path: &Path) -> PathBuf {
+    let backup = with_suffix(path, ".bak");
+    if !backup.exists() {
+        return backup;
+    }
+    with_suffix(path, &format!(".{}.bak", Local::now().format("%Y%m%d-%H%M%S%.3f")))
+}
+
+/// Versión del archivo de tareas si es más nueva que [TASKS_VERSION]
+fn newer_version(contents: &str) -> Option<u64> {
+    let value: serde_json::Value = serde_json::from_str(contents).ok()?;
+    value.get("version")?.as_u64().filter(|version| *version > TASKS_VERSION)
+}
+
+/// Convierte el contenido del archivo de tareas en un vector de [Task], migrando los formatos antiguos
+pub fn parse_tasks(contents: &str) -> Result<Vec<Task>, serde_json::Error> {
+    migrate(serde_json::from_str(contents)?)
+}
+
+/// Contenido del archivo de tareas en la versión actual del formato
+#[derive(Serialize)]
+struct TasksFile<'a> {
+    version: u64,
+    tasks: Vec<&'a Task>,
+}
+
+/// Convierte las tareas al formato actual del archivo, `{ "version": 1, "tasks": [...] }`
+pub fn tasks_to_json(tasks: Vec<&Task>) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&TasksFile { version: TASKS_VERSION, tasks })
+}
+
+/// Actualiza el contenido de un archivo de tareas de cualquier versión a [TASKS_VERSION].
+/// * Versión 0: el arreglo de tareas sin envoltorio, usado antes de versionar el archivo.
+/// * Versión 1: `{ "version": 1, "tasks": [...] }`.
+///
+/// Los campos nuevos que solo necesitan un valor por defecto se resuelven con `#[serde(default)]`;
+/// las transformaciones reales se agregan como un paso por versión antes de deserializar.
+pub fn migrate(value: serde_json::Value) -> Result<Vec<Task>, serde_json::Error> {
+    use serde::de::Error;
+
+    let (version, tasks) = match value {
+        serde_json::Value::Array(_) => (0, value),
+        serde_json::Value::Object(mut file) => {
+            let version = file.get("version").and_then(serde_json::Value::as_u64)
+                .ok_or_else(|| serde_json::Error::custom("falta la versión del archivo de tareas"))?;
+            (version, file.remove("tasks").unwrap_or_default())
+        }
+        _ => return Err(serde_json::Error::custom("el archivo de tareas no tiene un formato conocido"))
+    };
+
+    if version > TASKS_VERSION {
+        return Err(serde_json::Error::custom(format!(
+            "el archivo de tareas es de la versión {}, más nueva que la soportada ({})", version, TASKS_VERSION
+        )));
+    }
+
+    // Los pasos de una versión a la siguiente se aplican aquí en orden, p. ej. `if version < 2 { ... }`.
+    // De la 0 a la 1 solo se agregó el envoltorio, así que las tareas no necesitan cambios.
+
+    serde_json::from_value(tasks)
+}
+
+/// Une dos listas de tareas por id. Si una tarea está en ambas se conserva la de `modified_at` más reciente
+/// (ante un empate, la local), y las que están en una sola de las listas se incluyen tal cual.
+/// El resultado sigue el orden de `local`, seguido de las tareas que solo existen en `incoming`.
+pub fn merge_tasks(local: Vec<Task>, incoming: Vec<Task>) -> Vec<Task> {
+    let mut incoming: Vec<Option<Task>> = incoming.into_iter().map(Some).collect();
+
+    let mut merged: Vec<Task> = local.into_iter().map(|task| {
+        let other = incoming.iter_mut().find(|other| other.as_ref().is_some_and(|other| other.id() == task.id()));
+        match other.and_then(Option::take) {
+            Some(other) if other.modified_at() > task.modified_at() => other,
+            _ => task
+        }
+    }).collect();
+
+    merged.extend(incoming.into_iter().flatten());
+    merged
+}
+
+/// Huella del archivo en `path`: su fecha de modificación y un hash de su contenido.
+/// Permite detectar cambios externos e ignorar las escrituras propias.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileStamp {
+    pub modified: Option<SystemTime>,
+    pub hash: u64,
+}
+
+impl FileStamp {
+    pub fn new(modified: Option<SystemTime>, contents: &str) -> Self {
+        let mut hasher = DefaultHasher::new();
+        contents.hash(&mut hasher);
+
+        FileStamp { modified, hash: hasher.finish() }
+    }
+
+    /// Lee el archivo en `path` y obtiene su huella junto a su contenido
+    pub fn read(path: &Path) -> io::Result<(Self, String)> {
+        let modified = fs::metadata(path)?.modified().ok();
+        let contents = fs::read_to_string(path)?;
+
+        Ok((FileStamp::new(modified, &contents), contents))
+    }
+}
+
+/// Fecha de modificación del archivo en `path`, si existe
+pub fn modified_at(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Sobreescribe el archivo de tareas en `path` con el vector de tareas pasado como parámetro.
+/// El JSON se escribe indentado para que el archivo sea legible y fácil de comparar en git.
+pub fn write_tasks(path: &Path, tasks: Vec<&Task>) -> io::Result<()> {
+    write_atomic(path, &tasks_to_json(tasks)?)
+}
+
+/// Escribe `contents` en un archivo temporal junto a `path` y luego lo renombra sobre `path`,
+/// de modo que una interrupción a mitad de la escritura no deje el archivo a medias.
+pub fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let tmp = with_suffix(path, ".tmp");
+
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+/// Indica si `text` contiene `query` sin distinguir mayúsculas ni minúsculas.
+/// Los espacios al inicio y al final de `query` se ignoran.
+pub fn contains_ignore_case(text: &str, query: &str) -> bool {
+    text.to_lowercase().contains(&query.trim().to_lowercase())
+}
+
+/// Divide `text` en partes, marcando con `true` las que coinciden con alguna palabra de `query`
+/// sin distinguir mayúsculas. Si `query` está vacío se retorna el texto completo sin marcar.
+pub fn split_matches<'a>(text: &'a str, query: &str) -> Vec<(&'a str, bool)> {
+    let mut terms: Vec<Vec<char>> = query.split_whitespace()
+        .map(|term| term.chars().flat_map(char::to_lowercase).collect())
+        .collect();
+    // Probar primero las palabras más largas para resaltar la coincidencia completa
+    terms.sort_by_key(|term| std::cmp::Reverse(term.len()));
+
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    // Busca si alguna palabra empieza en `index` y retorna el índice del carácter siguiente al final
+    let match_at = |index: usize| terms.iter().find_map(|term| {
+        let (mut end, mut matched) = (index, 0);
+        while matched < term.len() && end < chars.len() {
+            let lower: Vec<char> = chars[end].1.to_lowercase().collect();
+            if !term[matched..].starts_with(&lower) {
+                return None
+            }
+            matched += lower.len();
+            end += 1;
+        }
+        (matched == term.len()).then_some(end)
+    });
+
+    let mut parts = Vec::new();
+    let (mut start, mut index) = (0, 0);
+    while index < chars.len() {
+        match match_at(index) {
+            Some(end) => {
+                let from = chars[index].0;
+                let to = chars.get(end).map_or(text.len(), |(byte, _)| *byte);
+                if start < from {
+                    parts.push((&text[start..from], false));
+                }
+                parts.push((&text[from..to], true));
+                start = to;
+                index = end;
+            }
+            None => index += 1
+        }
+    }
+    if start < text.len() || parts.is_empty() {
+        parts.push((&text[start..], false));
+    }
+
+    parts
+}
+
+/// Estilo de un fragmento en la vista previa de la descripción
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub struct Emphasis {
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool
+}
+
+/// Divide `text` en fragmentos según el énfasis simple de Markdown: `**negrita**`, `*cursiva*` y `` `código` ``.
+/// Los saltos de línea se conservan y los delimitadores sin cerrar se dejan tal cual.
+pub fn parse_emphasis(text: &str) -> Vec<(String, Emphasis)> {
+    let mut parts = Vec::new();
+    push_emphasis(text, Emphasis::default(), &mut parts);
+    parts
+}
+
+fn push_emphasis(text: &str, style: Emphasis, parts: &mut Vec<(String, Emphasis)>) {
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        // El delimitador debe ir pegado al texto y cerrarse más adelante, p. ej. "2 * 3" no es cursiva
+        let emphasized = ["**", "`", "*"].into_iter().find_map(|delimiter| {
+            let inner = rest.strip_prefix(delimiter)?;
+            if inner.starts_with(char::is_whitespace) {
+                return None
+            }
+            let mut end = inner.find(delimiter).filter(|end| *end > 0)?;
+            // En "***" el cierre es el final de la serie, así "**muy *importante***" cierra ambos
+            while inner[end + delimiter.len()..].starts_with(&delimiter[..1]) {
+                end += 1;
+            }
+            Some((delimiter, &inner[..end], &inner[end + delimiter.len()..]))
         });
 
-    serde_json::from_str(&tasks).unwrap()
+        match emphasized {
+            Some((delimiter, inner, after)) => {
+                if !plain.is_empty() {
+                    parts.push((std::mem::take(&mut plain), style));
+                }
+                match delimiter {
+                    "`" => parts.push((inner.to_string(), Emphasis { code: true, ..style })),
+                    "**" => push_emphasis(inner, Emphasis { bold: true, ..style }, parts),
+                    _ => push_emphasis(inner, Emphasis { italic: true, ..style }, parts)
+                }
+                rest = after;
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    if !plain.is_empty() {
+        parts.push((plain, style));
+    }
+}
+
+/// Puntaje de coincidencia de `query` en `text`, sin distinguir mayúsculas. Mayor es mejor.
+/// * Si `text` contiene `query` textualmente el puntaje es alto.
+/// * Si no, cada palabra de `query` debe coincidir con alguna palabra de `text`, ya sea
+///   como subcadena, como subsecuencia ("pryecto" -> "proyecto") o con pocos errores de tipeo.
+///
+/// Retorna [None] si no hay coincidencia. Una búsqueda vacía coincide con todo.
+pub fn match_score(text: &str, query: &str) -> Option<u32> {
+    let query = query.trim().to_lowercase();
+    let text = text.to_lowercase();
+
+    if query.is_empty() {
+        return Some(0);
+    }
+    if text.contains(&query) {
+        return Some(if text.starts_with(&query) {110} else {100});
+    }
+
+    let words: Vec<&str> = text.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).collect();
+    let mut total = 0;
+    let mut count = 0;
+
+    for query_word in query.split_whitespace() {
+        let best = words.iter().filter_map(|word| word_score(word, query_word)).max()?;
+        total += best;
+        count += 1;
+    }
+
+    Some(total / count.max(1))
+}
+
+/// Puntaje de coincidencia aproximada entre una palabra del texto y una de la búsqueda
+fn word_score(word: &str, query: &str) -> Option<u32> {
+    if word.contains(query) {
+        return Some(30);
+    }
+    // Las palabras muy cortas solo coinciden textualmente para evitar ruido
+    let length = query.chars().count();
+    if length < 3 {
+        return None;
+    }
+    if is_subsequence(query, word) {
+        return Some(20);
+    }
+    if edit_distance(word, query) <= (length / 4).max(1) {
+        return Some(10);
+    }
+    None
+}
+
+/// Indica si todos los caracteres de `needle` aparecen en orden dentro de `haystack`
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
+
+/// Distancia de edición entre dos palabras, contando como un solo error
+/// el intercambio de dos letras vecinas ("infrome" -> "informe")
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distance = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in distance.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in distance[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] {0} else {1};
+            distance[i][j] = (distance[i - 1][j] + 1)
+                .min(distance[i][j - 1] + 1)
+                .min(distance[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance[i][j] = distance[i][j].min(distance[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    distance[a.len()][b.len()]
+}
+
+/// Convierte una lista de etiquetas separadas por comas en un vector,
+/// recortando espacios y omitiendo entradas vacías o repetidas.
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+
+    for tag in input.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+
+    tags
+}
+
+/// Resultado de [parse_quick_add]: el título limpio y los atributos encontrados en el texto
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParsedTask {
+    pub title: String,
+    pub priority: Option<Priority>,
+    pub tags: Vec<String>,
+    pub due_date: Option<NaiveDateTime>,
 }
 
-/// Sobreescribe el archivo "tasks.json" con el vector de tareas pasado como parámetro
-pub fn write_tasks(tasks: Vec<&Task>) {
-    fs::write("tasks.json", serde_json::to_string(&tasks).unwrap()).unwrap();
+/// Extrae prioridad, etiquetas y fecha límite del título de una tarea nueva, ver [parse_quick_add_at]
+pub fn parse_quick_add(input: &str) -> ParsedTask {
+    parse_quick_add_at(input, Local::now().date_naive())
 }
 
-/// Convierte el tipo [NaiveDateTime] en [String] con el formato `%A %d de %B del %Y - %r`
-pub fn format_date_time(date_time: NaiveDateTime) -> String {
-    let date = date_time.date();
-    let time = date_time.time();
-    let date_localized = date.format_localized("%A %d de %B del %Y", Locale::es_PE).to_string();
-    let time_formated = time.format("%r").to_string();
+/// Reconoce, separados por espacios:
+/// * _`!alta`, `!media`, `!baja`_ (o `!high`, `!medium`, `!low`): prioridad; gana la última
+/// * _`#etiqueta`:_ agrega una etiqueta
+/// * _`due:hoy`, `due:mañana`, `due:AAAA-MM-DD` o `due:DD/MM/AAAA`:_ fecha límite, al final de ese día
+///
+/// Los demás tokens, incluidos los que tienen un valor no reconocido, quedan en el título.
+pub fn parse_quick_add_at(input: &str, today: NaiveDate) -> ParsedTask {
+    let mut parsed = ParsedTask::default();
+    let mut words: Vec<&str> = Vec::new();
+
+    for word in input.split_whitespace() {
+        if let Some(priority) = word.strip_prefix('!').and_then(parse_priority) {
+            parsed.priority = Some(priority);
+        } else if let Some(tag) = word.strip_prefix('#').filter(|tag| !tag.is_empty()) {
+            if !parsed.tags.iter().any(|t| t == tag) {
+                parsed.tags.push(tag.to_string());
+            }
+        } else if let Some(date) = word.strip_prefix("due:").and_then(|date| parse_due_day(date, today)) {
+            parsed.due_date = Some(date.and_time(NaiveTime::from_hms_opt(23, 59, 0).unwrap()));
+        } else {
+            words.push(word);
+        }
+    }
+
+    parsed.title = words.join(" ");
+    parsed
+}
+
+fn parse_priority(word: &str) -> Option<Priority> {
+    match word.to_lowercase().as_str() {
+        "alta" | "high" => Some(Priority::High),
+        "media" | "medium" => Some(Priority::Medium),
+        "baja" | "low" => Some(Priority::Low),
+        _ => None
+    }
+}
+
+fn parse_due_day(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match word.to_lowercase().as_str() {
+        "hoy" | "today" => Some(today),
+        "mañana" | "manana" | "tomorrow" => today.succ_opt(),
+        date => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .or_else(|_| NaiveDate::parse_from_str(date, "%d/%m/%Y"))
+            .ok()
+    }
+}
+
+/// Convierte las tareas en un documento Markdown agrupado por estado.
+/// Cada tarea es un elemento de lista con casilla, marcada si la tarea está terminada.
+pub fn tasks_to_markdown(tasks: &[&Task]) -> String {
+    let mut markdown = String::from("# Lista de Tareas\n");
+
+    for status in [Status::ToDo, Status::InProgress, Status::Done] {
+        markdown.push_str(&format!("\n## {}\n\n", status));
+
+        for task in tasks.iter().filter(|task| task.status == status) {
+            let check = if task.status == Status::Done {"x"} else {" "};
+            markdown.push_str(&format!("- [{}] **{}**\n", check, task.title));
+
+            for line in task.description.lines().filter(|line| !line.trim().is_empty()) {
+                markdown.push_str(&format!("  {}\n", line.trim_end()));
+            }
+            markdown.push_str(&format!("  _Creado: {}_\n", format_timestamp(task.created_at(), locale())));
+        }
+    }
+
+    markdown
+}
 
-    return format!("{} - {}", date_localized, time_formated);
-}
\ No newline at end of file
+/// Texto plano con los datos principales de la tarea, para copiarlo al portapapeles:
+/// título, estado y fechas en líneas separadas, y la descripción al final tras una línea en blanco
+pub fn task_to_clipboard_text(task: &Task) -> String {
+    let mut text = format!("{}\nEstado: {}\n", task.title, task.status);
+
+    if let Some(due_date) = task.due_date {
+        text.push_str(&format!("Vence: {}\n", format_date_time(due_date, locale())));
+    }
+    text.push_str(&format!("Creado: {}\n", format_timestamp(task.created_at(), locale())));
+    if let Some(completed_at) = task.completed_at() {
+        text.push_str(&format!("Completado: {}\n", format_timestamp(completed_at, locale())));
+    }
+
+    if !task.description.trim().is_empty() {
+        text.push_str(&format!("\n{}\n", task.description.trim()));
+    }
+    text
+}
+
+/// Exporta las tareas a un archivo Markdown en `path`
+pub fn export_markdown(tasks: &[&Task], path: &Path) -> io::Result<()> {
+    write_atomic(path, &tasks_to_markdown(tasks))
+}
+
+/// Errores que pueden ocurrir al importar tareas desde un CSV.
+/// * _`Csv:`_ El archivo no se pudo leer o no tiene el formato esperado
+/// * _`Field:`_ Un campo de la fila `line` no es válido
+#[derive(Debug)]
+pub enum CsvError {
+    Csv(csv::Error),
+    Field { line: u64, message: String },
+}
+
+impl Display for CsvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsvError::Csv(error) => write!(f, "No se pudo leer el CSV: {}", error),
+            CsvError::Field { line, message } => write!(f, "Error en la línea {}: {}", line, message),
+        }
+    }
+}
+
+impl From<csv::Error> for CsvError {
+    fn from(error: csv::Error) -> Self {
+        CsvError::Csv(error)
+    }
+}
+
+/// Fila del CSV de tareas, con todos los campos como texto.
+#[derive(Serialize, Deserialize)]
+struct CsvRecord {
+    id: String,
+    title: String,
+    description: String,
+    status: String,
+    created_at: String,
+    modified_at: String,
+}
+
+/// Formato de fecha usado en el CSV
+const CSV_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+/// Exporta las tareas a un archivo CSV en `path` con las columnas
+/// `id`, `title`, `description`, `status`, `created_at` y `modified_at`.
+pub fn export_csv(tasks: &[&Task], path: &Path) -> io::Result<()> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    for task in tasks {
+        writer.serialize(CsvRecord {
+            id: task.id().to_string(),
+            title: task.title.clone(),
+            description: task.description.clone(),
+            status: serde_json::to_value(task.status)?.as_str().unwrap_or_default().to_string(),
+            created_at: utc_to_local(task.created_at()).format(CSV_DATE_FORMAT).to_string(),
+            modified_at: utc_to_local(task.modified_at()).format(CSV_DATE_FORMAT).to_string(),
+        })?;
+    }
+
+    let contents = writer.into_inner().map_err(|error| error.into_error())?;
+    write_atomic(path, &String::from_utf8_lossy(&contents))
+}
+
+/// Importa tareas desde un archivo CSV con el mismo formato que [export_csv].
+/// Si la columna `id` está vacía se genera un nuevo identificador, y si las fechas están vacías se usa la fecha actual.
+/// Las fechas del CSV están en hora local, igual que al exportar.
+pub fn import_csv(path: &Path) -> Result<Vec<Task>, CsvError> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let headers = reader.headers()?.clone();
+    let mut tasks = Vec::new();
+
+    for record in reader.records() {
+        // La línea donde empieza el registro, que no coincide con el número de registro si una descripción tiene saltos de línea
+        let record = record?;
+        let line = record.position().map_or(0, |position| position.line());
+        let record: CsvRecord = record.deserialize(Some(&headers))?;
+        let field_error = |message: String| CsvError::Field { line, message };
+
+        let id = match record.id.trim() {
+            "" => Uuid::new_v4(),
+            id => Uuid::parse_str(id).map_err(|error| field_error(error.to_string()))?
+        };
+        let status = record.status.parse().map_err(field_error)?;
+        let now = Utc::now().naive_utc();
+        let parse_date = |date: &str| match date.trim() {
+            "" => Ok(now),
+            date => NaiveDateTime::parse_from_str(date, CSV_DATE_FORMAT)
+                .map(local_to_utc)
+                .map_err(|error| field_error(error.to_string()))
+        };
+
+        tasks.push(Task::restore(
+            id,
+            record.title,
+            record.description,
+            status,
+            parse_date(&record.created_at)?,
+            parse_date(&record.modified_at)?,
+        ));
+    }
+
+    Ok(tasks)
+}
+
+/// Línea de un archivo JSON Lines que no es una tarea válida. `line` empieza en 1.
+#[derive(Debug)]
+pub struct JsonlError {
+    pub line: usize,
+    pub error: serde_json::Error,
+}
+
+impl Display for JsonlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error en la línea {}: {}", self.line, self.error)
+    }
+}
+
+/// Exporta las tareas a `path` en formato JSON Lines: una tarea por línea en JSON compacto,
+/// con el mismo formato que el archivo de tareas.
+pub fn export_jsonl(tasks: &[&Task], path: &Path) -> io::Result<()> {
+    let mut contents = String::new();
+    for task in tasks {
+        contents.push_str(&serde_json::to_string(task)?);
+        contents.push('\n');
+    }
+
+    write_atomic(path, &contents)
+}
+
+/// Importa tareas desde un archivo JSON Lines como el de [export_jsonl], omitiendo las líneas en blanco.
+/// Una línea inválida no detiene la importación: se retorna junto a las tareas leídas.
+pub fn import_jsonl(path: &Path) -> io::Result<(Vec<Task>, Vec<JsonlError>)> {
+    let contents = fs::read_to_string(path)?;
+    let (mut tasks, mut errors) = (Vec::new(), Vec::new());
+
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(task) => tasks.push(task),
+            Err(error) => errors.push(JsonlError { line: index + 1, error })
+        }
+    }
+
+    Ok((tasks, errors))
+}
+
+/// Tarea en el formato que exportan otras aplicaciones (Todoist, Things). Los demás campos se ignoran.
+#[derive(Deserialize)]
+struct ExternalTask {
+    content: String,
+    #[serde(default)]
+    description: Option<String>,
+    /// Algunas versiones usan `true`/`false` y otras `1`/`0`
+    #[serde(default)]
+    checked: serde_json::Value,
+}
+
+/// Convierte la exportación de otra aplicación, un arreglo JSON de objetos con `content`, `description` y `checked`,
+/// en tareas nuevas: `content` es el título y `checked` indica si está terminada.
+/// Cada tarea recibe un id nuevo y la fecha actual; los elementos sin título se omiten.
+pub fn import_external(json: &str) -> Result<Vec<Task>, serde_json::Error> {
+    let external: Vec<ExternalTask> = serde_json::from_str(json)?;
+
+    Ok(external.into_iter()
+        .filter(|external| !external.content.trim().is_empty())
+        .map(|external| {
+            let mut task = Task::new(external.content, external.description.unwrap_or_default());
+            if external.checked == true || external.checked == 1 {
+                task.set_status(Status::Done);
+            }
+            task
+        })
+        .collect())
+}
+
+/// Recorta `text` a `max_chars` caracteres como máximo, terminando en "…" si se recortó
+pub fn truncate_chars(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Limpia una descripción: quita los espacios al inicio y al final, los espacios al final de
+/// cada línea y deja como máximo una línea en blanco seguida
+pub fn normalize_description(text: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.trim().lines().map(str::trim_end) {
+        if line.is_empty() && lines.last().is_some_and(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Cantidad de palabras y de caracteres de `text`, sin contar los espacios finales
+pub fn text_stats(text: &str) -> (usize, usize) {
+    let text = text.trim_end();
+
+    (text.split_whitespace().count(), text.chars().count())
+}
+
+/// Recorta `text` a `max_lines` líneas y `max_chars` caracteres, agregando "…" al final.
+/// Si el texto ya cabe, retorna [None].
+pub fn clamp_text(text: &str, max_lines: usize, max_chars: usize) -> Option<String> {
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.len() <= max_lines && text.chars().count() <= max_chars {
+        return None;
+    }
+
+    let clamped: String = lines[..lines.len().min(max_lines)].join("\n").chars().take(max_chars).collect();
+    Some(format!("{}…", clamped.trim_end()))
+}
+
+/// Idioma de las fechas, leído una sola vez de [LOCALE_ENV]. Si no está definido o no es válido se usa [DEFAULT_LOCALE].
+pub fn locale() -> Locale {
+    static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+    *LOCALE.get_or_init(|| {
+        env::var(LOCALE_ENV).ok()
+            .and_then(|locale| Locale::try_from(locale.trim()).ok())
+            .unwrap_or(DEFAULT_LOCALE)
+    })
+}
+
+/// Formato sin nombres localizados que usa [format_date_time] si el formato localizado falla
+pub const FALLBACK_DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Parte de la fecha del formato por defecto, con los nombres de días y meses localizados
+pub const DEFAULT_DATE_FORMAT: &str = "%A %d de %B del %Y";
+
+/// Parte de la hora del formato por defecto. No se localiza, porque no todos los idiomas
+/// tienen reloj de 12 horas (en es_ES, por ejemplo, `%r` localizado queda vacío).
+pub const DEFAULT_TIME_FORMAT: &str = "%r";
+
+/// Formato de fechas por defecto, ver [crate::settings::Preferences::date_time_format].
+/// Es [DEFAULT_DATE_FORMAT] y [DEFAULT_TIME_FORMAT] separados por " - ", y siempre se muestra con [format_date_time].
+pub const DEFAULT_DATE_TIME_FORMAT: &str = "%A %d de %B del %Y - %r";
+
+/// Convierte el tipo [NaiveDateTime] en [String] con el formato [DEFAULT_DATE_TIME_FORMAT]:
+/// la fecha con los nombres de días y meses en el idioma `locale` y la hora sin localizar.
+/// Si chrono no puede aplicar el formato se usa [FALLBACK_DATE_TIME_FORMAT] en lugar de entrar en pánico.
+pub fn format_date_time(date_time: NaiveDateTime, locale: Locale) -> String {
+    try_format_localized(date_time, DEFAULT_DATE_FORMAT, DEFAULT_TIME_FORMAT, locale)
+        .unwrap_or_else(|_| date_time.format(FALLBACK_DATE_TIME_FORMAT).to_string())
+}
+
+/// Igual que [format_date_time], pero con un formato de chrono elegido por el usuario.
+/// Si `format` es [DEFAULT_DATE_TIME_FORMAT], está vacío o no es válido se usa el formato por defecto.
+pub fn format_date_time_as(date_time: NaiveDateTime, format: &str, locale: Locale) -> String {
+    if format == DEFAULT_DATE_TIME_FORMAT {
+        return format_date_time(date_time, locale);
+    }
+    try_format_date_time(date_time, format, locale).unwrap_or_else(|_| format_date_time(date_time, locale))
+}
+
+/// Da formato a una fecha y hora local con `format` y los nombres en el idioma `locale`.
+/// Retorna [fmt::Error] si el formato está vacío o no es válido, o si la hora no existe en la zona local.
+pub fn try_format_date_time(date_time: NaiveDateTime, format: &str, locale: Locale) -> Result<String, fmt::Error> {
+    let local = Local.from_local_datetime(&date_time).earliest().ok_or(fmt::Error)?;
+    if format.trim().is_empty() {
+        return Err(fmt::Error);
+    }
+
+    let mut formatted = String::new();
+    write!(formatted, "{}", local.format_localized(format, locale))?;
+    Ok(formatted)
+}
+
+/// Indica si [format_date_time_as] puede usar `format` en lugar del formato por defecto
+pub fn is_valid_date_time_format(format: &str) -> bool {
+    try_format_date_time(NaiveDateTime::default(), format, DEFAULT_LOCALE).is_ok()
+}
+
+/// Da formato a la fecha con los nombres en el idioma `locale` y a la hora sin localizar, separadas por " - ".
+/// Retorna [fmt::Error] si algún formato no es válido, en vez del pánico de `to_string`.
+pub fn try_format_localized(date_time: NaiveDateTime, date_format: &str, time_format: &str, locale: Locale) -> Result<String, fmt::Error> {
+    let mut formatted = String::new();
+    write!(formatted, "{} - {}", date_time.date().format_localized(date_format, locale), date_time.time().format(time_format))?;
+    Ok(formatted)
+}
+
+/// Convierte un instante UTC a la hora local del sistema
+pub fn utc_to_local(date_time: NaiveDateTime) -> NaiveDateTime {
+    Local.from_utc_datetime(&date_time).naive_local()
+}
+
+/// Convierte una hora local del sistema a UTC. Si la hora no existe
+/// (p. ej. en un cambio de horario) se deja tal cual.
+pub fn local_to_utc(date_time: NaiveDateTime) -> NaiveDateTime {
+    Local.from_local_datetime(&date_time)
+        .earliest()
+        .map(|local| local.naive_utc())
+        .unwrap_or(date_time)
+}
+
+/// Formatea un instante guardado en UTC (como `created_at`) en la hora local con [format_date_time]
+pub fn format_timestamp(date_time: NaiveDateTime, locale: Locale) -> String {
+    format_date_time(utc_to_local(date_time), locale)
+}
+
+/// Igual que [format_timestamp], con el formato de [format_date_time_as]
+pub fn format_timestamp_as(date_time: NaiveDateTime, format: &str, locale: Locale) -> String {
+    format_date_time_as(utc_to_local(date_time), format, locale)
+}
+
+/// Formatea una duración como "1h 20m", "45m" o "menos de 1m"
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+
+    match (minutes / 60, minutes % 60) {
+        (0, 0) => "menos de 1m".to_string(),
+        (0, minutes) => format!("{}m", minutes),
+        (hours, minutes) => format!("{}h {}m", hours, minutes)
+    }
+}
+
+/// Formatea una antigüedad en días ("1 día", "12 días"), o como [format_duration] si es menos de un día
+pub fn format_age(age: TimeDelta) -> String {
+    match age.num_days() {
+        0 => format_duration(age.to_std().unwrap_or_default()),
+        1 => "1 día".to_string(),
+        days => format!("{} días", days)
+    }
+}
+
+/// Formatea una cantidad de minutos como "1h 30m", "2h" o "45m"
+pub fn format_minutes(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h {}m", hours, minutes)
+    }
+}
+
+/// Describe el instante UTC `date_time` respecto al momento actual, como "hace 5 minutos" o "ayer".
+/// Para fechas de hace más de una semana, o futuras, se usa [format_timestamp_as] con `format`.
+pub fn format_relative(date_time: NaiveDateTime, format: &str, locale: Locale) -> String {
+    format_relative_to(date_time, Utc::now().naive_utc(), format, locale)
+}
+
+/// Igual que [format_relative], tomando `now` (en UTC) como el momento actual
+pub fn format_relative_to(date_time: NaiveDateTime, now: NaiveDateTime, format: &str, locale: Locale) -> String {
+    let elapsed = now - date_time;
+    let plural = |count: i64, singular: &str, plural: &str| {
+        format!("hace {} {}", count, if count == 1 {singular} else {plural})
+    };
+
+    if elapsed < TimeDelta::zero() || elapsed >= TimeDelta::weeks(1) {
+        format_timestamp_as(date_time, format, locale)
+    }else if elapsed < TimeDelta::minutes(1) {
+        "justo ahora".to_string()
+    }else if elapsed < TimeDelta::hours(1) {
+        plural(elapsed.num_minutes(), "minuto", "minutos")
+    }else if elapsed < TimeDelta::days(1) {
+        plural(elapsed.num_hours(), "hora", "horas")
+    }else if elapsed < TimeDelta::days(2) {
+        "ayer".to_string()
+    }else {
+        plural(elapsed.num_days(), "día", "días")
+    }
+}
+
+/// (De)serialización de los instantes de [Task] guardados en UTC.
+///
+/// Se escriben en RFC 3339 con sufijo `Z`. Los archivos anteriores guardaban la hora local
+/// sin zona horaria; esas fechas se interpretan como hora local y se convierten a UTC al leerlas,
+/// así que un archivo antiguo se migra al volver a guardarse sin desplazar las horas dos veces.
+pub mod utc_timestamp {
+    use chrono::{DateTime, NaiveDateTime};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use super::local_to_utc;
+
+    const FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.fZ";
+
+    pub fn serialize<S: Serializer>(date_time: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&date_time.format(FORMAT).to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDateTime, D::Error> {
+        parse(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+
+    /// Acepta fechas con zona horaria (p. ej. `2024-05-20T12:00:00Z`) o la hora local sin zona del formato antiguo
+    pub fn parse(value: &str) -> Result<NaiveDateTime, chrono::ParseError> {
+        match DateTime::parse_from_rfc3339(value) {
+            Ok(date_time) => Ok(date_time.naive_utc()),
+            Err(_) => value.parse::<NaiveDateTime>().map(local_to_utc)
+        }
+    }
+
+    /// Igual que el módulo padre, para campos opcionales
+    pub mod option {
+        use chrono::NaiveDateTime;
+        use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(date_time: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error> {
+            match date_time {
+                Some(date_time) => super::serialize(date_time, serializer),
+                None => serializer.serialize_none()
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NaiveDateTime>, D::Error> {
+            Option::<String>::deserialize(deserializer)?
+                .map(|value| super::parse(&value).map_err(D::Error::custom))
+                .transpose()
+        }
+    }
+}
+
+/// (De)serialización de una [Duration] como segundos enteros
+pub mod duration_seconds {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_secs())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_secs)
+    }
+}